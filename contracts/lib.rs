@@ -5,12 +5,40 @@ mod anonymous_poll {
     use ink::prelude::string::String;
     use ink::prelude::vec::Vec;
     use ink::storage::Mapping;
-    use ink::env::call::{build_call_sol, ExecutionInput, Selector};
-    use ink::env::DefaultEnvironment;
-    use ink::H160;
+    use ink::{H160, U256};
+
+    /// Maximum number of poll ids accepted by batch read messages
+    pub const MAX_BATCH_SIZE: usize = 50;
+
+    /// How long an ended poll's live result rows are kept before they may be pruned (30 days)
+    pub const RESULT_RETENTION_MS: u64 = 30 * 24 * 60 * 60 * 1000;
+
+    /// Number of samples kept per option when history tracking is enabled
+    pub const MAX_HISTORY_SAMPLES: usize = 32;
+
+    /// Upper bound on the size of a decompressed proof
+    pub const MAX_PROOF_SIZE: usize = 32 * 1024;
+
+    /// BN254 scalar field modulus, the field every public input must fit in (decimal)
+    pub const BN254_MODULUS: &str =
+        "21888242871839275222246405745257275088548364400416034343698204186575808495617";
+
+    /// `BN254_MODULUS` as big-endian bytes, for comparing against 32-byte field inputs
+    pub const BN254_MODULUS_BYTES: [u8; 32] = [
+        0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+        0x28, 0x33, 0xe8, 0x48, 0x79, 0xb9, 0x70, 0x91, 0x43, 0xe1, 0xf5, 0x93, 0xf0, 0x00, 0x00, 0x01,
+    ];
+
+    /// Depth of each poll's nullifier accumulator tree
+    pub const NULLIFIER_TREE_DEPTH: usize = 32;
+
+    /// Version of the compressed proof layout read by `cast_vote_compressed`,
+    /// sent as the first byte of every compressed proof
+    pub const PROOF_FORMAT_VERSION: u32 = 1;
 
     /// Storage for the anonymous poll contract
     #[ink(storage)]
+    #[allow(clippy::type_complexity)]
     pub struct AnonymousPoll {
         /// Address of the deployed Solidity verifier contract
         verifier_address: H160,
@@ -20,10 +48,92 @@ mod anonymous_poll {
         next_poll_id: u128,
         /// Mapping from poll_id to Poll data
         polls: Mapping<u128, Poll>,
-        /// Mapping from (poll_id, nullifier_hash) to bool - tracks used nullifiers
-        used_nullifiers: Mapping<(u128, [u8; 32]), bool>,
+        /// Mapping from (poll_id, nullifier_hash) to the block the vote was recorded in - tracks used nullifiers
+        used_nullifiers: Mapping<(u128, [u8; 32]), BlockNumber>,
         /// Mapping from poll_id to vote tallies (option_index -> vote_count)
-        vote_tallies: Mapping<(u128, u32), u32>,
+        vote_tallies: Mapping<(u128, u32), u64>,
+        /// Mapping from (poll_id, voter) to bool - tracks voters of non-anonymous polls
+        voted: Mapping<(u128, H160), bool>,
+        /// Proof systems creators may select for their polls
+        allowed_proof_systems: Vec<ProofSystem>,
+        /// Binds proofs to this deployment to prevent cross-contract replay
+        domain_separator: [u8; 32],
+        /// Mapping from (poll_id, account) to the grant epoch in which the account was allowed
+        /// to read embargoed results
+        results_viewers: Mapping<(u128, H160), u32>,
+        /// Mapping from poll_id to the tallies frozen when the poll ended
+        final_results: Mapping<u128, Vec<u64>>,
+        /// Mapping from poll_id to the timestamp the poll was closed at
+        closed_at: Mapping<u128, u64>,
+        /// Mapping from poll_id to the block the poll was closed in
+        closed_at_block: Mapping<u128, BlockNumber>,
+        /// Mapping from (poll_id, account) to the grant epoch in which the creator delegated
+        /// the account as co-admin
+        poll_admins: Mapping<(u128, H160), u32>,
+        /// Global kill-switch blocking all voting
+        paused: bool,
+        /// Mapping from creator to the ids of the polls they own
+        polls_by_creator: Mapping<H160, Vec<u128>>,
+        /// Mapping from (poll_id, option_index) to recent (block, count) samples, oldest first
+        option_history: Mapping<(u128, u32), Vec<(BlockNumber, u64)>>,
+        /// Mapping from (poll_id, keccak256 of proof bytes) to bool - proofs already submitted to deduplicating polls
+        proof_hash_seen: Mapping<(u128, [u8; 32]), bool>,
+        /// Number of options the deployed circuit was compiled for
+        circuit_max_options: u32,
+        /// Reject polls whose option count differs from `circuit_max_options`
+        require_exact_options: bool,
+        /// Mapping from poll_id to bool - polls paused by their creator or a poll admin
+        paused_polls: Mapping<u128, bool>,
+        /// Chain the contract was deployed for, bound into every proof's public inputs
+        chain_id: u32,
+        /// Mapping from (poll_id, checkpoint_index) to the tallies published at that checkpoint
+        checkpoints: Mapping<(u128, u32), Vec<u64>>,
+        /// Mapping from poll_id to the number of checkpoints published
+        checkpoint_count: Mapping<u128, u32>,
+        /// Mapping from poll_id to (block, votes seen in that block) - only kept for rate limited polls
+        block_votes: Mapping<u128, (BlockNumber, u32)>,
+        /// Number of polls that have not been closed yet
+        active_polls: u32,
+        /// Mapping from poll_id to the part of its prize pool already paid out
+        prize_claimed: Mapping<u128, U256>,
+        /// Mapping from poll_id to the number of leaves in its incremental keccak256
+        /// Merkle tree of used nullifiers
+        nullifier_leaf_count: Mapping<u128, u64>,
+        /// Mapping from (poll_id, level) to that tree's left frontier node at the level
+        nullifier_frontier: Mapping<(u128, u8), [u8; 32]>,
+        /// Largest proof accepted for verification
+        max_proof_bytes: u32,
+        /// Mapping from (poll_id, nullifier) to the option the nullifier voted for - only kept for polls storing choices
+        vote_choices: Mapping<(u128, [u8; 32]), u32>,
+        /// Smallest proof accepted for verification
+        min_proof_bytes: u32,
+        /// Sum of every poll's unclaimed prize, which `withdraw` may not touch
+        prize_reserved: U256,
+        /// Mapping from poll_id to the proof-of-work difficulty (leading zero bytes) open votes must meet
+        pow_difficulty: Mapping<u128, u8>,
+        /// Mapping from (poll_id, nullifier) to bool - nullifiers of voters found ineligible after creation
+        revoked_nullifiers: Mapping<(u128, [u8; 32]), bool>,
+        /// Mapping from poll_id to how long after its end time votes are still taken as late votes (ms)
+        grace_periods: Mapping<u128, u64>,
+        /// Mapping from (poll_id, option_index) to votes cast during the grace period, kept out of the tally
+        late_tallies: Mapping<(u128, u32), u64>,
+        /// Mapping from poll_id to the minimum count an option needs to be viable
+        viability_thresholds: Mapping<u128, u64>,
+        /// Addresses that have created at least one poll
+        seen_creator: Mapping<H160, bool>,
+        /// Number of distinct addresses that have created a poll
+        distinct_creators: u32,
+        /// Polls that keep a vote count for every block they receive votes in
+        block_counts_enabled: Mapping<u128, bool>,
+        /// Mapping from (poll_id, block) to votes tallied in that block
+        votes_in_block: Mapping<(u128, BlockNumber), u32>,
+        /// Polls whose vote events carry a zeroed nullifier instead of the real one
+        hidden_event_nullifiers: Mapping<u128, bool>,
+        /// Mapping from poll_id to its current grant epoch. Admin and viewer grants from an
+        /// earlier epoch are void, so bumping it on transfer drops every grant at once.
+        grant_epochs: Mapping<u128, u32>,
+        /// Mapping from poll_id to the account it has been offered to by `transfer_poll`
+        pending_transfers: Mapping<u128, H160>,
     }
 
     /// Poll structure
@@ -32,6 +142,7 @@ mod anonymous_poll {
         feature = "std",
         derive(ink::storage::traits::StorageLayout)
     )]
+    #[derive(ink::SolEncode, ink::SolDecode, Clone, Debug, PartialEq, Eq)]
     pub struct Poll {
         /// Unique poll identifier
         pub id: u128,
@@ -48,15 +159,174 @@ mod anonymous_poll {
         /// Whether the poll is active
         pub is_active: bool,
         /// Total votes cast
-        pub total_votes: u32,
+        pub total_votes: u64,
         /// Poll creation timestamp
         pub created_at: u64,
         /// Poll end timestamp (0 means no end time)
         pub ends_at: u64,
+        /// Optional behaviour configured by the creator
+        pub settings: PollSettings,
+        /// Value attached at creation, held for later distribution
+        pub prize_pool: U256,
+    }
+
+    /// Optional poll settings, changeable by the creator until the first vote
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(ink::SolEncode, ink::SolDecode, Clone, Debug, PartialEq, Eq)]
+    pub struct PollSettings {
+        /// Whether voters stay anonymous; when false the caller's address is recorded
+        pub anonymous: bool,
+        /// Number of eligible voters in the merkle tree (0 means unknown)
+        pub eligible_count: u32,
+        /// Proof system the poll's proofs are generated with
+        pub proof_system: ProofSystem,
+        /// Hide results from the public until the poll is over
+        pub embargo_results: bool,
+        /// Keep recent per-option counts for live charts (costs storage on every vote)
+        pub track_history: bool,
+        /// Accept votes without an eligibility proof via `cast_open_vote`
+        pub open: bool,
+        /// Minimum total votes for a valid outcome (0 means no quorum)
+        pub quorum: u64,
+        /// Minimum number of options that must receive votes for a valid outcome
+        pub min_options_voted: u32,
+        /// Reject resubmitted proof bytes before verification (costs storage on every vote)
+        pub dedupe_proofs: bool,
+        /// Maximum votes accepted per block to smooth load (0 means unlimited)
+        pub max_votes_per_block: u32,
+        /// Record each nullifier's chosen option, e.g. for receipts (costs storage on every vote)
+        pub store_choices: bool,
+        /// Close the poll as soon as any option reaches this many votes (0 means never)
+        pub auto_close_threshold: u64,
+    }
+
+    impl Default for PollSettings {
+        fn default() -> Self {
+            Self {
+                anonymous: true,
+                eligible_count: 0,
+                proof_system: ProofSystem::default(),
+                embargo_results: false,
+                track_history: false,
+                open: false,
+                quorum: 0,
+                min_options_voted: 0,
+                dedupe_proofs: false,
+                max_votes_per_block: 0,
+                store_choices: false,
+                auto_close_threshold: 0,
+            }
+        }
+    }
+
+    /// Contract-wide configuration, returned by `get_contract_info`
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[derive(ink::SolEncode, ink::SolDecode, Clone, Debug, PartialEq, Eq)]
+    pub struct ContractInfo {
+        /// Contract owner/admin
+        pub owner: H160,
+        /// Whether voting is globally paused
+        pub paused: bool,
+        /// Address of the deployed Solidity verifier contract
+        pub verifier_address: H160,
+        /// Id the next created poll will get
+        pub next_poll_id: u128,
+        /// Number of polls created so far
+        pub total_polls: u128,
+        /// Number of polls that have not been closed yet
+        pub active_polls: u32,
+        /// Number of distinct addresses that have created a poll
+        pub distinct_creators: u32,
+    }
+
+    /// A nullifier's standing in a poll, returned by `get_voter_status`
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[derive(ink::SolEncode, ink::SolDecode, Clone, Debug, PartialEq, Eq)]
+    pub struct VoterStatus {
+        /// Whether a vote with this nullifier would pass the poll's checks right now
+        pub can_vote: bool,
+        /// Whether the nullifier has already been used
+        pub nullifier_used: bool,
+        /// Whether `recorded_choice` holds the nullifier's vote
+        pub has_recorded_choice: bool,
+        /// Option the nullifier voted for (0 when none is recorded)
+        pub recorded_choice: u32,
+    }
+
+    /// Global circuit configuration, applied atomically by `set_circuit_config`
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[derive(ink::SolEncode, ink::SolDecode, Clone, Debug, PartialEq, Eq)]
+    pub struct CircuitConfig {
+        /// Number of options the deployed circuit was compiled for
+        pub max_options: u32,
+        /// Reject polls whose option count differs from `max_options`
+        pub require_exact_options: bool,
+        /// Largest proof accepted for verification, at most `MAX_PROOF_SIZE`
+        pub max_proof_bytes: u32,
+        /// Smallest proof accepted for verification (0 means only empty proofs are rejected)
+        pub min_proof_bytes: u32,
+        /// Proof systems creators may select for their polls
+        pub allowed_proof_systems: Vec<ProofSystem>,
+    }
+
+    /// Input and storage caps enforced by the contract, returned by `get_limits`
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[derive(ink::SolEncode, ink::SolDecode, Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct Limits {
+        /// Most poll ids read or accepted by batch messages (`MAX_BATCH_SIZE`)
+        pub max_batch_size: u32,
+        /// Largest decompressed proof in bytes (`MAX_PROOF_SIZE`)
+        pub max_proof_size: u32,
+        /// Samples kept per option by history tracking (`MAX_HISTORY_SAMPLES`)
+        pub max_history_samples: u32,
+    }
+
+    /// Validity of a poll's result, returned by `get_poll_outcome`
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[derive(ink::SolEncode, ink::SolDecode, Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum PollOutcome {
+        /// Poll is still running
+        Pending,
+        /// Fewer votes than the poll's quorum
+        FailedQuorum,
+        /// Votes spread across fewer options than required
+        FailedSpread,
+        /// All outcome rules are met
+        Valid,
+    }
+
+    /// How `get_results_with_percentages` rounds basis-point shares
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[derive(ink::SolEncode, ink::SolDecode, Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum RoundingMode {
+        /// Round every share down; shares may sum to less than 10000
+        Floor,
+        /// Largest-remainder apportionment; shares sum to exactly 10000 once any vote exists
+        LargestRemainder,
+    }
+
+    /// Proving backends a poll can be configured for
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(ink::storage::traits::StorageLayout)
+    )]
+    #[derive(ink::SolEncode, ink::SolDecode, Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub enum ProofSystem {
+        /// Barretenberg UltraHonk, matching the deployed Solidity verifier
+        #[default]
+        UltraHonk,
+        /// Barretenberg UltraPlonk
+        UltraPlonk,
     }
 
     /// Errors
     #[ink::error]
+    #[derive(Debug, PartialEq, Eq)]
     pub enum Error {
         /// Only the owner can perform this action
         OnlyOwner,
@@ -78,6 +348,64 @@ mod anonymous_poll {
         InvalidMerkleRoot,
         /// Invalid poll parameters
         InvalidPollParameters,
+        /// A vote count would overflow
+        VoteCountOverflow,
+        /// Poll has not reached its end time yet
+        PollNotEnded,
+        /// Too many items passed to a batch message
+        BatchTooLarge,
+        /// Compressed proof bytes are malformed
+        DecompressionFailed,
+        /// Only the poll creator can perform this action
+        OnlyCreator,
+        /// Poll already has votes
+        PollHasVotes,
+        /// Proof system is not enabled by the owner
+        ProofSystemNotAllowed,
+        /// Results are embargoed until the poll is over
+        ResultsEmbargoed,
+        /// Poll ended too recently to be pruned
+        RetentionNotElapsed,
+        /// Poll is not open and requires an anonymous membership proof
+        NotAnonymousProofRequired,
+        /// Voting is globally paused by the owner
+        ContractPaused,
+        /// The same proof bytes were already submitted to this poll
+        ProofAlreadySubmitted,
+        /// Voting on this poll is paused by its creator
+        PollPaused,
+        /// No proof bytes were submitted
+        EmptyProof,
+        /// Cross-contract call to the allowlist failed
+        AllowlistCallFailed,
+        /// Nullifier is malformed, e.g. a copy of the poll's merkle root
+        InvalidNullifierFormat,
+        /// Poll's per-block vote limit is reached, retry in a later block
+        RateLimited,
+        /// Payout exceeds the poll's unclaimed prize
+        InsufficientPrize,
+        /// Transferring value out of the contract failed
+        TransferFailed,
+        /// Amount exceeds the contract's balance not reserved for prizes
+        InsufficientBalance,
+        /// Proof is larger than the configured maximum
+        ProofTooLarge,
+        /// Proof is smaller than the configured minimum
+        ProofTooSmall,
+        /// Poll does not store vote choices
+        ChoiceNotStored,
+        /// Nullifier does not meet the poll's proof-of-work difficulty
+        InsufficientProofOfWork,
+        /// Voter's eligibility was revoked by the poll creator
+        EligibilityRevoked,
+        /// Replacing the contract code failed
+        UpgradeFailed,
+        /// Poll is open and takes votes through `cast_open_vote`, not the proof path
+        WrongVotingMode,
+        /// Compressed proof declares a format version this contract does not read
+        UnsupportedProofFormat,
+        /// The caller has not been offered this poll
+        NoPendingTransfer,
     }
 
     /// Events
@@ -91,6 +419,16 @@ mod anonymous_poll {
 
     #[ink(event)]
     pub struct VoteCast {
+        #[ink(topic)]
+        poll_id: u128,
+        #[ink(topic)]
+        creator: H160,
+        nullifier: [u8; 32],
+        timestamp: u64,
+    }
+
+    #[ink(event)]
+    pub struct LateVote {
         #[ink(topic)]
         poll_id: u128,
         nullifier: [u8; 32],
@@ -101,26 +439,226 @@ mod anonymous_poll {
     pub struct PollClosed {
         #[ink(topic)]
         poll_id: u128,
-        total_votes: u32,
+        total_votes: u64,
+    }
+
+    #[ink(event)]
+    pub struct PollTransferred {
+        #[ink(topic)]
+        poll_id: u128,
+        from: H160,
+        to: H160,
+    }
+
+    #[ink(event)]
+    pub struct VerifierUpdated {
+        #[ink(topic)]
+        updated_by: H160,
+        verifier: H160,
+    }
+
+    #[ink(event)]
+    pub struct ContractUpgraded {
+        code_hash: [u8; 32],
+    }
+
+    #[ink(event)]
+    pub struct QuorumReached {
+        #[ink(topic)]
+        poll_id: u128,
+        total_votes: u64,
+    }
+
+    #[ink(event)]
+    pub struct CircuitConfigUpdated {
+        config: CircuitConfig,
+    }
+
+    #[ink(event)]
+    pub struct CheckpointPublished {
+        #[ink(topic)]
+        poll_id: u128,
+        index: u32,
+        block: BlockNumber,
+    }
+
+    /// Inflate a compressed proof.
+    /// Format: 1-byte `PROOF_FORMAT_VERSION`, 4-byte big-endian decompressed length,
+    /// then `(run_length, byte)` pairs, where `run_length` is at least 1.
+    fn decompress_proof(data: &[u8]) -> Result<Vec<u8>, Error> {
+        let (version, data) = data.split_first().ok_or(Error::DecompressionFailed)?;
+        if u32::from(*version) != PROOF_FORMAT_VERSION {
+            return Err(Error::UnsupportedProofFormat);
+        }
+
+        let (header, body) = data
+            .split_first_chunk::<4>()
+            .ok_or(Error::DecompressionFailed)?;
+        let expected_len = u32::from_be_bytes(*header) as usize;
+
+        if expected_len > MAX_PROOF_SIZE || body.len() % 2 != 0 {
+            return Err(Error::DecompressionFailed);
+        }
+
+        let mut proof = Vec::with_capacity(expected_len);
+        for pair in body.chunks_exact(2) {
+            let (run_length, byte) = (pair[0] as usize, pair[1]);
+            if run_length == 0 || proof.len() + run_length > expected_len {
+                return Err(Error::DecompressionFailed);
+            }
+            proof.resize(proof.len() + run_length, byte);
+        }
+
+        if proof.len() != expected_len {
+            return Err(Error::DecompressionFailed);
+        }
+
+        Ok(proof)
+    }
+
+    /// Share of `part` in `whole` in basis points (1/100 of a percent).
+    /// Uses u128 intermediates so large u64 counts cannot overflow; returns 0 when
+    /// `whole` is 0 and saturates at `u32::MAX` if the share does not fit.
+    fn bps(part: u64, whole: u64) -> u32 {
+        if whole == 0 {
+            return 0;
+        }
+        let share = u128::from(part) * 10_000 / u128::from(whole);
+        u32::try_from(share).unwrap_or(u32::MAX)
+    }
+
+    /// Selector of a Solidity function signature.
+    /// Note that this is a const function, it is evaluated at compile time.
+    #[cfg(not(test))]
+    const fn solidity_selector(fn_sig: &str) -> [u8; 4] {
+        let output: [u8; 32] = const_crypto::sha3::Keccak256::new()
+            .update(fn_sig.as_bytes())
+            .finalize();
+        [output[0], output[1], output[2], output[3]]
+    }
+
+    /// Basis-point shares of `counts` apportioned by largest remainder: every option
+    /// gets its floored share, then the leftover points go to the largest remainders
+    /// (lower option index first on ties). Sums to exactly 10000 unless all counts are 0.
+    fn largest_remainder_bps(counts: &[u64]) -> Vec<u32> {
+        let total: u128 = counts.iter().map(|count| u128::from(*count)).sum();
+        if total == 0 {
+            return ink::prelude::vec![0; counts.len()];
+        }
+
+        // Floored shares are at most 10000, so they fit in a u32
+        let mut shares: Vec<u32> = counts
+            .iter()
+            .map(|count| (u128::from(*count) * 10_000 / total) as u32)
+            .collect();
+        let leftover = 10_000 - shares.iter().sum::<u32>();
+
+        let mut by_remainder: Vec<usize> = (0..counts.len()).collect();
+        by_remainder.sort_by_key(|&option| core::cmp::Reverse(u128::from(counts[option]) * 10_000 % total));
+        for option in by_remainder.into_iter().take(leftover as usize) {
+            shares[option] += 1;
+        }
+        shares
     }
 
     impl AnonymousPoll {
         /// Constructor
         #[ink(constructor)]
         pub fn new(verifier_address: H160) -> Self {
-            let caller = Self::env().caller();
+            Self::new_with_chain_id(verifier_address, 0)
+        }
+
+        /// Constructor for factory deployments, where the deployer is not the intended owner
+        #[ink(constructor)]
+        pub fn new_with_owner(verifier_address: H160, owner: H160) -> Self {
+            Self::init(verifier_address, 0, owner)
+        }
+
+        /// Constructor for single-poll deployments (one contract per poll): creates poll 1
+        /// in the deployment transaction, with the deployer as its creator
+        #[ink(constructor)]
+        pub fn new_single_poll(
+            verifier_address: H160,
+            title: String,
+            description: String,
+            merkle_root: [u8; 32],
+            max_options: u32,
+            duration_seconds: u64,
+        ) -> Result<Self, Error> {
+            let mut contract = Self::new_with_chain_id(verifier_address, 0);
+            contract.create_poll(title, description, merkle_root, max_options, duration_seconds)?;
+            Ok(contract)
+        }
+
+        /// Constructor binding proofs to a chain id, so proofs cannot be replayed on a fork
+        #[ink(constructor)]
+        pub fn new_with_chain_id(verifier_address: H160, chain_id: u32) -> Self {
+            Self::init(verifier_address, chain_id, Self::env().caller())
+        }
+
+        /// Shared constructor body. The initial verifier is attributed to `owner`,
+        /// which may differ from the deployer.
+        fn init(verifier_address: H160, chain_id: u32, owner: H160) -> Self {
+            // Left-pad the contract's own address so it reads as a single field element
+            let mut domain_separator = [0u8; 32];
+            domain_separator[12..].copy_from_slice(Self::env().address().as_bytes());
+
+            Self::env().emit_event(VerifierUpdated {
+                updated_by: owner,
+                verifier: verifier_address,
+            });
+
             Self {
                 verifier_address,
-                owner: caller,
+                owner,
                 next_poll_id: 1,
                 polls: Mapping::default(),
                 used_nullifiers: Mapping::default(),
                 vote_tallies: Mapping::default(),
+                voted: Mapping::default(),
+                allowed_proof_systems: ink::prelude::vec![ProofSystem::UltraHonk],
+                domain_separator,
+                results_viewers: Mapping::default(),
+                final_results: Mapping::default(),
+                closed_at: Mapping::default(),
+                closed_at_block: Mapping::default(),
+                poll_admins: Mapping::default(),
+                paused: false,
+                polls_by_creator: Mapping::default(),
+                option_history: Mapping::default(),
+                proof_hash_seen: Mapping::default(),
+                circuit_max_options: 0,
+                require_exact_options: false,
+                paused_polls: Mapping::default(),
+                chain_id,
+                checkpoints: Mapping::default(),
+                checkpoint_count: Mapping::default(),
+                block_votes: Mapping::default(),
+                active_polls: 0,
+                prize_claimed: Mapping::default(),
+                nullifier_leaf_count: Mapping::default(),
+                nullifier_frontier: Mapping::default(),
+                max_proof_bytes: MAX_PROOF_SIZE as u32,
+                vote_choices: Mapping::default(),
+                min_proof_bytes: 0,
+                prize_reserved: U256::zero(),
+                pow_difficulty: Mapping::default(),
+                revoked_nullifiers: Mapping::default(),
+                grace_periods: Mapping::default(),
+                late_tallies: Mapping::default(),
+                viability_thresholds: Mapping::default(),
+                seen_creator: Mapping::default(),
+                distinct_creators: 0,
+                block_counts_enabled: Mapping::default(),
+                votes_in_block: Mapping::default(),
+                hidden_event_nullifiers: Mapping::default(),
+                grant_epochs: Mapping::default(),
+                pending_transfers: Mapping::default(),
             }
         }
 
-        /// Create a new poll
-        #[ink(message)]
+        /// Create a new poll. Any value transferred becomes the poll's prize pool.
+        #[ink(message, payable)]
         pub fn create_poll(
             &mut self,
             title: String,
@@ -129,31 +667,18 @@ mod anonymous_poll {
             max_options: u32,
             duration_seconds: u64,
         ) -> Result<u128, Error> {
-            // Enforce string length limits to prevent payload size issues
-            if title.len() > 100 {
-                return Err(Error::InvalidPollParameters);
-            }
-            
-            if description.len() > 500 {
-                return Err(Error::InvalidPollParameters);
-            }
-            
-            if max_options == 0 || max_options > 100 {
-                return Err(Error::InvalidPollParameters);
-            }
-
-            if merkle_root == [0u8; 32] {
-                return Err(Error::InvalidMerkleRoot);
-            }
+            self.validate_poll_config(
+                title.clone(),
+                description.clone(),
+                merkle_root,
+                max_options,
+                duration_seconds,
+            )?;
 
             let poll_id = self.next_poll_id;
             let caller = self.env().caller();
             let now = self.env().block_timestamp();
-            let ends_at = if duration_seconds > 0 {
-                now + duration_seconds * 1000 // Convert to milliseconds
-            } else {
-                0 // No end time
-            };
+            let ends_at = self.end_time(duration_seconds)?;
 
             let poll = Poll {
                 id: poll_id,
@@ -166,10 +691,23 @@ mod anonymous_poll {
                 total_votes: 0,
                 created_at: now,
                 ends_at,
+                settings: PollSettings::default(),
+                prize_pool: self.env().transferred_value(),
             };
 
+            self.prize_reserved = self.prize_reserved.saturating_add(poll.prize_pool);
             self.polls.insert(poll_id, &poll);
             self.next_poll_id += 1;
+            self.active_polls = self.active_polls.saturating_add(1);
+
+            let mut creator_polls = self.polls_by_creator.get(caller).unwrap_or_default();
+            creator_polls.push(poll_id);
+            self.polls_by_creator.insert(caller, &creator_polls);
+
+            if !self.seen_creator.contains(caller) {
+                self.seen_creator.insert(caller, &true);
+                self.distinct_creators = self.distinct_creators.saturating_add(1);
+            }
 
             // Initialize vote tallies for all options
             for option in 0..max_options {
@@ -185,6 +723,97 @@ mod anonymous_poll {
             Ok(poll_id)
         }
 
+        /// Create a new poll whose `eligible_count` is snapshotted from an allowlist contract.
+        /// The allowlist must implement `eligibleCount() returns (uint32)`.
+        #[ink(message, payable)]
+        pub fn create_poll_with_allowlist(
+            &mut self,
+            title: String,
+            description: String,
+            merkle_root: [u8; 32],
+            max_options: u32,
+            duration_seconds: u64,
+            allowlist: H160,
+        ) -> Result<u128, Error> {
+            let eligible_count = self.call_allowlist(allowlist)?;
+            let poll_id = self.create_poll(title, description, merkle_root, max_options, duration_seconds)?;
+
+            let mut poll = self.polls.get(poll_id).ok_or(Error::PollNotFound)?;
+            poll.settings.eligible_count = eligible_count;
+            self.polls.insert(poll_id, &poll);
+
+            Ok(poll_id)
+        }
+
+        /// Run every `create_poll` validation without creating anything, so
+        /// integrators can check a poll configuration up front
+        #[ink(message)]
+        pub fn validate_poll_config(
+            &self,
+            title: String,
+            description: String,
+            merkle_root: [u8; 32],
+            max_options: u32,
+            duration_seconds: u64,
+        ) -> Result<(), Error> {
+            // Enforce string length limits to prevent payload size issues
+            if title.len() > 100 {
+                return Err(Error::InvalidPollParameters);
+            }
+            
+            if description.len() > 500 {
+                return Err(Error::InvalidPollParameters);
+            }
+            
+            if max_options == 0 || max_options > 100 {
+                return Err(Error::InvalidPollParameters);
+            }
+
+            // A fixed-size circuit can never verify votes for a different option count
+            if self.require_exact_options && max_options != self.circuit_max_options {
+                return Err(Error::InvalidPollParameters);
+            }
+
+            Self::validate_merkle_root(merkle_root)?;
+
+            // New polls start on the default proof system, which the owner may have disabled
+            if !self.allowed_proof_systems.contains(&ProofSystem::default()) {
+                return Err(Error::ProofSystemNotAllowed);
+            }
+
+            self.end_time(duration_seconds)?;
+
+            Ok(())
+        }
+
+        /// Check that a merkle root is nonzero and a BN254 field element
+        fn validate_merkle_root(merkle_root: [u8; 32]) -> Result<(), Error> {
+            if merkle_root == [0u8; 32] {
+                return Err(Error::InvalidMerkleRoot);
+            }
+
+            // The circuit's Pedersen root is a field element; larger values can never verify
+            if merkle_root >= BN254_MODULUS_BYTES {
+                return Err(Error::InvalidMerkleRoot);
+            }
+
+            Ok(())
+        }
+
+        /// Compute a new poll's end timestamp in milliseconds (0 means no end time).
+        /// A nonzero duration always ends after the current block, so the only degenerate
+        /// case is an end that does not fit in a u64 timestamp.
+        fn end_time(&self, duration_seconds: u64) -> Result<u64, Error> {
+            if duration_seconds == 0 {
+                return Ok(0);
+            }
+
+            duration_seconds
+                .checked_mul(1000)
+                .and_then(|duration| self.env().block_timestamp().checked_add(duration))
+                .ok_or(Error::InvalidPollParameters)
+        }
+
         /// Cast a vote with zero-knowledge proof
         #[ink(message)]
         pub fn cast_vote(
@@ -194,16 +823,87 @@ mod anonymous_poll {
             nullifier: [u8; 32],
             vote_choice: u32,
         ) -> Result<(), Error> {
-            // Get poll and validate
-            let mut poll = self.polls.get(poll_id).ok_or(Error::PollNotFound)?;
+            let poll = self.validate_vote(poll_id, nullifier, vote_choice)?;
+            if poll.settings.open {
+                return Err(Error::WrongVotingMode);
+            }
+            self.verify_proof(&poll, proof, nullifier)?;
+            self.record_vote(poll, nullifier, vote_choice)
+        }
+
+        /// Cast a vote with a run-length encoded proof to save calldata.
+        /// The proof is inflated with `decompress_proof` and then handled like `cast_vote`.
+        #[ink(message)]
+        pub fn cast_vote_compressed(
+            &mut self,
+            poll_id: u128,
+            compressed_proof: Vec<u8>,
+            nullifier: [u8; 32],
+            vote_choice: u32,
+        ) -> Result<(), Error> {
+            let poll = self.validate_vote(poll_id, nullifier, vote_choice)?;
+            if poll.settings.open {
+                return Err(Error::WrongVotingMode);
+            }
+            let proof = decompress_proof(&compressed_proof)?;
+            self.verify_proof(&poll, proof, nullifier)?;
+            self.record_vote(poll, nullifier, vote_choice)
+        }
+
+        /// Cast a vote in an open poll, which has no eligibility proof.
+        /// The nullifier is a client-chosen random value that only guards against
+        /// the same submission being counted twice.
+        #[ink(message)]
+        pub fn cast_open_vote(
+            &mut self,
+            poll_id: u128,
+            nullifier: [u8; 32],
+            vote_choice: u32,
+        ) -> Result<(), Error> {
+            let poll = self.validate_vote(poll_id, nullifier, vote_choice)?;
+
+            if !poll.settings.open {
+                return Err(Error::NotAnonymousProofRequired);
+            }
+
+            let difficulty = self.pow_difficulty.get(poll_id).unwrap_or(0);
+            if !self.meets_pow(poll_id, nullifier, difficulty) {
+                return Err(Error::InsufficientProofOfWork);
+            }
+
+            self.record_vote(poll, nullifier, vote_choice)
+        }
+
+        /// Check that keccak256(poll_id || nullifier) starts with `difficulty` zero bytes.
+        /// Binding the poll id stops work done for one poll being reused in another.
+        fn meets_pow(&self, poll_id: u128, nullifier: [u8; 32], difficulty: u8) -> bool {
+            let mut input = [0u8; 48];
+            input[..16].copy_from_slice(&poll_id.to_be_bytes());
+            input[16..].copy_from_slice(&nullifier);
+            let hash = self.env().hash_bytes::<ink::env::hash::Keccak256>(&input);
+            hash.iter().take(difficulty as usize).all(|byte| *byte == 0)
+        }
+
+        /// Run the cheap storage checks for a vote.
+        /// Every vote entry point calls this before doing any work on the proof,
+        /// so malformed or duplicate votes fail fast.
+        fn validate_vote(&self, poll_id: u128, nullifier: [u8; 32], vote_choice: u32) -> Result<Poll, Error> {
+            if self.paused {
+                return Err(Error::ContractPaused);
+            }
+
+            let poll = self.polls.get(poll_id).ok_or(Error::PollNotFound)?;
             
             if !poll.is_active {
                 return Err(Error::PollNotActive);
             }
 
-            // Check if poll has ended
-            let now = self.env().block_timestamp();
-            if poll.ends_at > 0 && now > poll.ends_at {
+            if self.paused_polls.contains(poll_id) {
+                return Err(Error::PollPaused);
+            }
+
+            // Check if poll has ended; votes in the grace period are still taken as late votes
+            if self.is_expired(&poll) && !self.in_grace_period(&poll) {
                 return Err(Error::PollEnded);
             }
 
@@ -212,17 +912,58 @@ mod anonymous_poll {
                 return Err(Error::InvalidVoteOption);
             }
 
+            if poll.settings.max_votes_per_block > 0
+                && self.votes_in_current_block(poll_id) >= poll.settings.max_votes_per_block
+            {
+                return Err(Error::RateLimited);
+            }
+
+            // A nullifier equal to the merkle root is a client copy-paste bug
+            if nullifier == poll.merkle_root {
+                return Err(Error::InvalidNullifierFormat);
+            }
+
+            if self.revoked_nullifiers.contains((poll_id, nullifier)) {
+                return Err(Error::EligibilityRevoked);
+            }
+
             // Check if nullifier has been used (prevents double voting)
-            if self.used_nullifiers.get((poll_id, nullifier)).unwrap_or(false) {
+            if self.used_nullifiers.contains((poll_id, nullifier)) {
                 return Err(Error::NullifierAlreadyUsed);
             }
 
+            Ok(poll)
+        }
+
+        /// Verify a vote's proof against the poll's public inputs
+        fn verify_proof(&mut self, poll: &Poll, proof: Vec<u8>, nullifier: [u8; 32]) -> Result<(), Error> {
+            // A common client bug, worth telling apart from a proof that fails to verify
+            if proof.is_empty() {
+                return Err(Error::EmptyProof);
+            }
+            if proof.len() > self.max_proof_bytes as usize {
+                return Err(Error::ProofTooLarge);
+            }
+            // Truncated proofs can never verify, so skip the verifier call
+            if proof.len() < self.min_proof_bytes as usize {
+                return Err(Error::ProofTooSmall);
+            }
+
+            // Resubmitted proof bytes can never carry a fresh nullifier, so skip the verifier call
+            if poll.settings.dedupe_proofs {
+                let proof_hash = self.env().hash_bytes::<ink::env::hash::Keccak256>(&proof);
+                if self.proof_hash_seen.contains((poll.id, proof_hash)) {
+                    return Err(Error::ProofAlreadySubmitted);
+                }
+                self.proof_hash_seen.insert((poll.id, proof_hash), &true);
+            }
+
             // Prepare public inputs for verification
-            // Public inputs: [merkle_root, nullifier, poll_id, max_options]
+            // Public inputs: [merkle_root, nullifier, poll_id, max_options, domain_separator, chain_id]
             let public_inputs = self.encode_public_inputs(
                 poll.merkle_root,
                 nullifier,
-                poll_id,
+                poll.id,
                 poll.max_options,
             );
 
@@ -233,40 +974,159 @@ mod anonymous_poll {
                 return Err(Error::ProofVerificationFailed);
             }
 
-            // Mark nullifier as used
-            self.used_nullifiers.insert((poll_id, nullifier), &true);
+            Ok(())
+        }
+
+        /// Record an already validated (and, for gated polls, verified) vote
+        fn record_vote(&mut self, mut poll: Poll, nullifier: [u8; 32], vote_choice: u32) -> Result<(), Error> {
+            let poll_id = poll.id;
+
+            // Consume the nullifier before any other state write, event or value
+            // handling, so a re-entrant call can never count the same vote twice
+            if self.used_nullifiers.contains((poll_id, nullifier)) {
+                return Err(Error::NullifierAlreadyUsed);
+            }
+            self.used_nullifiers.insert((poll_id, nullifier), &self.env().block_number());
+            self.append_nullifier_leaf(poll_id, nullifier)?;
+            if poll.settings.store_choices {
+                self.vote_choices.insert((poll_id, nullifier), &vote_choice);
+            }
+
+            // Pseudonymous polls also record who voted
+            if !poll.settings.anonymous {
+                self.voted.insert((poll_id, self.env().caller()), &true);
+            }
+
+            // The nullifier stays in storage either way; only the event copy is hidden
+            let event_nullifier = if self.hidden_event_nullifiers.contains(poll_id) {
+                [0u8; 32]
+            } else {
+                nullifier
+            };
+
+            // Late votes are kept apart until the creator decides to include them
+            if self.in_grace_period(&poll) {
+                let late = self.late_tallies.get((poll_id, vote_choice)).unwrap_or(0);
+                let late = late.checked_add(1).ok_or(Error::VoteCountOverflow)?;
+                self.late_tallies.insert((poll_id, vote_choice), &late);
+                self.env().emit_event(LateVote {
+                    poll_id,
+                    nullifier: event_nullifier,
+                    timestamp: self.env().block_timestamp(),
+                });
+                return Ok(());
+            }
 
             // Update vote tally
             let current_tally = self.vote_tallies.get((poll_id, vote_choice)).unwrap_or(0);
-            self.vote_tallies.insert((poll_id, vote_choice), &(current_tally + 1));
+            let new_tally = current_tally.checked_add(1).ok_or(Error::VoteCountOverflow)?;
+            self.vote_tallies.insert((poll_id, vote_choice), &new_tally);
+
+            if poll.settings.track_history {
+                self.record_history_sample(poll_id, vote_choice, new_tally);
+            }
+
+            if poll.settings.max_votes_per_block > 0 {
+                let votes = self.votes_in_current_block(poll_id) + 1;
+                self.block_votes.insert(poll_id, &(self.env().block_number(), votes));
+            }
+
+            if self.block_counts_enabled.contains(poll_id) {
+                let block = self.env().block_number();
+                let votes = self.votes_in_block.get((poll_id, block)).unwrap_or(0).saturating_add(1);
+                self.votes_in_block.insert((poll_id, block), &votes);
+            }
 
             // Update poll total votes
-            poll.total_votes += 1;
+            poll.total_votes = poll.total_votes.checked_add(1).ok_or(Error::VoteCountOverflow)?;
             self.polls.insert(poll_id, &poll);
 
             // Emit event
             self.env().emit_event(VoteCast {
                 poll_id,
-                nullifier,
-                timestamp: now,
+                creator: poll.creator,
+                nullifier: event_nullifier,
+                timestamp: self.env().block_timestamp(),
             });
 
+            // Totals only grow by one and the quorum is frozen once voting starts,
+            // so this fires exactly once per poll
+            if poll.settings.quorum > 0 && poll.total_votes == poll.settings.quorum {
+                self.env().emit_event(QuorumReached {
+                    poll_id,
+                    total_votes: poll.total_votes,
+                });
+            }
+
+            // First past the post: the winning vote closes the poll
+            if poll.settings.auto_close_threshold > 0 && new_tally >= poll.settings.auto_close_threshold {
+                self.close(poll);
+            }
+
+            Ok(())
+        }
+
+        /// Votes recorded for a rate limited poll in the current block.
+        /// A count from an earlier block reads as zero, so the limit resets each block.
+        fn votes_in_current_block(&self, poll_id: u128) -> u32 {
+            match self.block_votes.get(poll_id) {
+                Some((block, votes)) if block == self.env().block_number() => votes,
+                _ => 0,
+            }
+        }
+
+        /// Append a nullifier to the poll's accumulator tree. Only the one frontier
+        /// node that changes is written; nodes are read only where they are hashed in.
+        fn append_nullifier_leaf(&mut self, poll_id: u128, nullifier: [u8; 32]) -> Result<(), Error> {
+            let count = self.nullifier_leaf_count.get(poll_id).unwrap_or(0);
+            if count >= 1 << NULLIFIER_TREE_DEPTH {
+                return Err(Error::VoteCountOverflow);
+            }
+
+            let mut node = nullifier;
+            let mut index = count;
+            for level in 0..NULLIFIER_TREE_DEPTH as u8 {
+                if index & 1 == 0 {
+                    self.nullifier_frontier.insert((poll_id, level), &node);
+                    break;
+                }
+                let sibling = self.nullifier_frontier.get((poll_id, level)).unwrap_or_default();
+                node = self.hash_pair(&sibling, &node);
+                index >>= 1;
+            }
+
+            self.nullifier_leaf_count.insert(poll_id, &(count + 1));
             Ok(())
         }
 
+        /// keccak256 of two concatenated tree nodes
+        fn hash_pair(&self, left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+            let mut input = [0u8; 64];
+            input[..32].copy_from_slice(left);
+            input[32..].copy_from_slice(right);
+            self.env().hash_bytes::<ink::env::hash::Keccak256>(&input)
+        }
+
+        /// Append a (block, count) sample for an option, dropping the oldest once
+        /// `MAX_HISTORY_SAMPLES` are stored
+        fn record_history_sample(&mut self, poll_id: u128, option: u32, count: u64) {
+            let mut history = self.option_history.get((poll_id, option)).unwrap_or_default();
+            if history.len() >= MAX_HISTORY_SAMPLES {
+                history.remove(0);
+            }
+            history.push((self.env().block_number(), count));
+            self.option_history.insert((poll_id, option), &history);
+        }
+
         /// Call the Solidity verifier contract
+        #[cfg(not(test))]
         fn call_verifier(
             &self,
             proof: Vec<u8>,
             public_inputs: Vec<u8>,
         ) -> Result<bool, Error> {
-            /// Note that this is a const function, it is evaluated at compile time.
-            const fn solidity_selector(fn_sig: &str) -> [u8; 4] {
-                let output: [u8; 32] = const_crypto::sha3::Keccak256::new()
-                    .update(fn_sig.as_bytes())
-                    .finalize();
-                [output[0], output[1], output[2], output[3]]
-            }
+            use ink::env::call::{build_call_sol, ExecutionInput, Selector};
+            use ink::env::DefaultEnvironment;
 
             // Solidity function signature: verify(bytes calldata proof, bytes calldata publicInputs)
             let selector = solidity_selector("verify(bytes,bytes)");
@@ -288,6 +1148,59 @@ mod anonymous_poll {
             }
         }
 
+        /// Stand-in for the Solidity verifier in unit tests.
+        /// The off-chain environment cannot dispatch to a Solidity contract,
+        /// so a proof whose first byte is 1 is treated as valid.
+        #[cfg(test)]
+        fn call_verifier(
+            &self,
+            proof: Vec<u8>,
+            _public_inputs: Vec<u8>,
+        ) -> Result<bool, Error> {
+            Ok(proof.first() == Some(&1))
+        }
+
+        /// Read the number of eligible voters from an allowlist contract
+        #[cfg(not(test))]
+        fn call_allowlist(&self, allowlist: H160) -> Result<u32, Error> {
+            use ink::env::call::{build_call_sol, ExecutionInput, Selector};
+            use ink::env::DefaultEnvironment;
+
+            // Solidity function signature: eligibleCount() returns (uint32)
+            let selector = solidity_selector("eligibleCount()");
+
+            let result = build_call_sol::<DefaultEnvironment>()
+                .call(allowlist)
+                .exec_input(ExecutionInput::new(Selector::new(selector)))
+                .returns::<u32>()
+                .try_invoke();
+
+            match result {
+                Ok(Ok(count)) => Ok(count),
+                Ok(Err(_)) => Err(Error::AllowlistCallFailed),
+                Err(_) => Err(Error::AllowlistCallFailed),
+            }
+        }
+
+        /// Stand-in for an allowlist contract in unit tests.
+        /// The zero address is treated as unreachable; any other address reports
+        /// its first byte times 10 as the eligible count.
+        #[cfg(test)]
+        fn call_allowlist(&self, allowlist: H160) -> Result<u32, Error> {
+            if allowlist == H160::zero() {
+                return Err(Error::AllowlistCallFailed);
+            }
+            Ok(u32::from(allowlist.as_bytes()[0]) * 10)
+        }
+
+        /// Get the exact public inputs a vote's proof is verified against, without verifying anything.
+        /// The vote choice is a private circuit input, so only the nullifier is needed.
+        #[ink(message)]
+        pub fn debug_expected_inputs(&self, poll_id: u128, nullifier: [u8; 32]) -> Option<Vec<u8>> {
+            let poll = self.polls.get(poll_id)?;
+            Some(self.encode_public_inputs(poll.merkle_root, nullifier, poll.id, poll.max_options))
+        }
+
         /// Encode public inputs for the verifier
         /// Format: concatenate all public inputs as bytes
         fn encode_public_inputs(
@@ -310,225 +1223,4165 @@ mod anonymous_poll {
             
             // Add max_options (4 bytes for u32)
             inputs.extend_from_slice(&max_options.to_be_bytes());
+
+            // Add domain_separator (32 bytes)
+            inputs.extend_from_slice(&self.domain_separator);
+
+            // Add chain_id (4 bytes for u32)
+            inputs.extend_from_slice(&self.chain_id.to_be_bytes());
             
             inputs
         }
 
-        /// Close a poll (only creator or owner)
+        /// Close a poll (only creator, poll admin or owner)
         #[ink(message)]
         pub fn close_poll(&mut self, poll_id: u128) -> Result<(), Error> {
             let caller = self.env().caller();
-            let mut poll = self.polls.get(poll_id).ok_or(Error::PollNotFound)?;
+            let poll = self.polls.get(poll_id).ok_or(Error::PollNotFound)?;
 
-            // Only poll creator or contract owner can close
-            if caller != poll.creator && caller != self.owner {
+            // Only poll creator, poll admins or contract owner can close
+            if !self.can_manage_poll(&poll, caller) && caller != self.owner {
                 return Err(Error::OnlyOwner);
             }
 
-            poll.is_active = false;
+            self.close(poll);
+            Ok(())
+        }
+
+        /// Update a poll's optional settings (only creator or poll admin, before any vote is cast)
+        #[ink(message)]
+        pub fn update_poll_settings(&mut self, poll_id: u128, settings: PollSettings) -> Result<(), Error> {
+            let mut poll = self.polls.get(poll_id).ok_or(Error::PollNotFound)?;
+
+            if !self.can_manage_poll(&poll, self.env().caller()) {
+                return Err(Error::OnlyCreator);
+            }
+
+            if poll.total_votes > 0 {
+                return Err(Error::PollHasVotes);
+            }
+
+            if !self.allowed_proof_systems.contains(&settings.proof_system) {
+                return Err(Error::ProofSystemNotAllowed);
+            }
+
+            poll.settings = settings;
             self.polls.insert(poll_id, &poll);
+            Ok(())
+        }
 
-            self.env().emit_event(PollClosed {
-                poll_id,
-                total_votes: poll.total_votes,
-            });
+        /// Replace a poll's eligibility merkle root (only creator or poll admin, before any vote is cast).
+        /// Votes are only checked against the current root, so changing it mid-poll would
+        /// invalidate the proofs of voters who have not voted yet.
+        #[ink(message)]
+        pub fn update_merkle_root(&mut self, poll_id: u128, merkle_root: [u8; 32]) -> Result<(), Error> {
+            let mut poll = self.polls.get(poll_id).ok_or(Error::PollNotFound)?;
+
+            if !self.can_manage_poll(&poll, self.env().caller()) {
+                return Err(Error::OnlyCreator);
+            }
+
+            if !poll.is_active {
+                return Err(Error::PollNotActive);
+            }
+
+            if poll.total_votes > 0 {
+                return Err(Error::PollHasVotes);
+            }
+
+            Self::validate_merkle_root(merkle_root)?;
 
+            poll.merkle_root = merkle_root;
+            self.polls.insert(poll_id, &poll);
             Ok(())
         }
 
-        /// Get poll details
+        /// Require open votes to carry a proof-of-work nullifier with `difficulty` leading
+        /// zero bytes, to slow spam (only creator or poll admin, before any vote is cast)
         #[ink(message)]
-        pub fn get_poll(&self, poll_id: u128) -> (bool, u128, String, String, [u8; 32], u32, H160, bool, u32, u64, u64) {
-            match self.polls.get(poll_id) {
-                Some(poll) => (
-                    true,  // exists
-                    poll.id,
-                    poll.title,
-                    poll.description,
-                    poll.merkle_root,
-                    poll.max_options,
-                    poll.creator,
-                    poll.is_active,
-                    poll.total_votes,
-                    poll.created_at,
-                    poll.ends_at,
-                ),
-                None => (
-                    false,  // doesn't exist
-                    0,
-                    String::new(),
-                    String::new(),
-                    [0u8; 32],
-                    0,
-                    H160::from([0u8; 20]),
-                    false,
-                    0,
-                    0,
-                    0,
-                ),
+        pub fn set_pow_difficulty(&mut self, poll_id: u128, difficulty: u8) -> Result<(), Error> {
+            let poll = self.polls.get(poll_id).ok_or(Error::PollNotFound)?;
+            if !self.can_manage_poll(&poll, self.env().caller()) {
+                return Err(Error::OnlyCreator);
+            }
+            if poll.total_votes > 0 {
+                return Err(Error::PollHasVotes);
+            }
+            if difficulty > 32 {
+                return Err(Error::InvalidPollParameters);
             }
+            self.pow_difficulty.insert(poll_id, &difficulty);
+            Ok(())
         }
 
-        /// Get vote tally for a specific option
+        /// Get the proof-of-work difficulty open votes must meet (0 means none)
         #[ink(message)]
-        pub fn get_vote_tally(&self, poll_id: u128, option: u32) -> u32 {
-            self.vote_tallies.get((poll_id, option)).unwrap_or(0)
+        pub fn get_pow_difficulty(&self, poll_id: u128) -> u8 {
+            self.pow_difficulty.get(poll_id).unwrap_or(0)
         }
 
-        /// Get all vote tallies for a poll
+        /// Keep taking votes for `grace_ms` after the poll's end time, counted apart as late
+        /// votes (only creator or poll admin, before any vote is cast)
         #[ink(message)]
-        pub fn get_all_tallies(&self, poll_id: u128) -> Result<Vec<u32>, Error> {
+        pub fn set_grace_period(&mut self, poll_id: u128, grace_ms: u64) -> Result<(), Error> {
             let poll = self.polls.get(poll_id).ok_or(Error::PollNotFound)?;
-            let mut tallies = Vec::new();
-
-            for option in 0..poll.max_options {
-                let tally = self.vote_tallies.get((poll_id, option)).unwrap_or(0);
-                tallies.push(tally);
+            if !self.can_manage_poll(&poll, self.env().caller()) {
+                return Err(Error::OnlyCreator);
             }
-
-            Ok(tallies)
+            if poll.total_votes > 0 {
+                return Err(Error::PollHasVotes);
+            }
+            self.grace_periods.insert(poll_id, &grace_ms);
+            Ok(())
         }
 
-        /// Check if a nullifier has been used
+        /// Get the per-option counts of votes cast during the grace period
         #[ink(message)]
-        pub fn is_nullifier_used(&self, poll_id: u128, nullifier: [u8; 32]) -> bool {
-            self.used_nullifiers.get((poll_id, nullifier)).unwrap_or(false)
+        pub fn get_late_results(&self, poll_id: u128) -> Vec<u64> {
+            match self.polls.get(poll_id) {
+                Some(poll) => (0..poll.max_options)
+                    .map(|option| self.late_tallies.get((poll_id, option)).unwrap_or(0))
+                    .collect(),
+                None => Vec::new(),
+            }
         }
 
-        /// Get the verifier contract address
+        /// Add the late votes to the poll's results once the grace period is over
+        /// (only creator or poll admin). Returns how many votes were added.
         #[ink(message)]
-        pub fn get_verifier_address(&self) -> H160 {
-            self.verifier_address
+        pub fn include_late_votes(&mut self, poll_id: u128) -> Result<u64, Error> {
+            let mut poll = self.polls.get(poll_id).ok_or(Error::PollNotFound)?;
+            if !self.can_manage_poll(&poll, self.env().caller()) {
+                return Err(Error::OnlyCreator);
+            }
+            if !self.is_expired(&poll) || self.in_grace_period(&poll) {
+                return Err(Error::PollNotEnded);
+            }
+
+            let mut tallies = self.tallies(&poll);
+            let mut added = 0u64;
+            for (option, count) in tallies.iter_mut().enumerate() {
+                let late = self.late_tallies.take((poll_id, option as u32)).unwrap_or(0);
+                *count = count.checked_add(late).ok_or(Error::VoteCountOverflow)?;
+                added = added.checked_add(late).ok_or(Error::VoteCountOverflow)?;
+            }
+
+            // Results are read from the snapshot once one exists, otherwise from the live rows
+            if self.final_results.contains(poll_id) {
+                self.final_results.insert(poll_id, &tallies);
+            } else {
+                for (option, count) in tallies.iter().enumerate() {
+                    self.vote_tallies.insert((poll_id, option as u32), count);
+                }
+            }
+
+            poll.total_votes = poll.total_votes.checked_add(added).ok_or(Error::VoteCountOverflow)?;
+            self.polls.insert(poll_id, &poll);
+            Ok(added)
         }
 
-        /// Update verifier address (only owner)
+        /// Set the minimum count an option needs to be viable, e.g. to win a seat
+        /// (only creator or poll admin, before any vote is cast)
         #[ink(message)]
-        pub fn update_verifier(&mut self, new_verifier: H160) -> Result<(), Error> {
-            if self.env().caller() != self.owner {
-                return Err(Error::OnlyOwner);
+        pub fn set_viability_threshold(&mut self, poll_id: u128, threshold: u64) -> Result<(), Error> {
+            let poll = self.polls.get(poll_id).ok_or(Error::PollNotFound)?;
+            if !self.can_manage_poll(&poll, self.env().caller()) {
+                return Err(Error::OnlyCreator);
             }
-            self.verifier_address = new_verifier;
+            if poll.total_votes > 0 {
+                return Err(Error::PollHasVotes);
+            }
+            self.viability_thresholds.insert(poll_id, &threshold);
             Ok(())
         }
 
-        /// Get contract owner
+        /// Get the indices of the options whose count meets the poll's viability threshold
         #[ink(message)]
-        pub fn get_owner(&self) -> H160 {
-            self.owner
+        pub fn get_viable_options(&self, poll_id: u128) -> Option<Vec<u32>> {
+            let tallies = self.get_all_tallies(poll_id).ok()?;
+            let threshold = self.viability_thresholds.get(poll_id).unwrap_or(0);
+            Some(
+                (0u32..)
+                    .zip(tallies)
+                    .filter(|(_, count)| *count >= threshold)
+                    .map(|(option, _)| option)
+                    .collect(),
+            )
         }
 
-        /// Get total number of polls
+        /// Keep an on-chain vote count per block, for clients that cannot index `VoteCast`.
+        /// Costs an extra storage write per vote (only creator or poll admin, before any vote is cast)
         #[ink(message)]
-        pub fn get_total_polls(&self) -> u128 {
-            self.next_poll_id - 1
+        pub fn set_block_vote_counts(&mut self, poll_id: u128, enabled: bool) -> Result<(), Error> {
+            let poll = self.polls.get(poll_id).ok_or(Error::PollNotFound)?;
+            if !self.can_manage_poll(&poll, self.env().caller()) {
+                return Err(Error::OnlyCreator);
+            }
+            if poll.total_votes > 0 {
+                return Err(Error::PollHasVotes);
+            }
+            if enabled {
+                self.block_counts_enabled.insert(poll_id, &true);
+            } else {
+                self.block_counts_enabled.remove(poll_id);
+            }
+            Ok(())
         }
 
-        /// Check if poll has ended
+        /// Choose whether vote events carry the voter's nullifier. Hiding it stops observers
+        /// linking events to nullifiers; `is_nullifier_used` still works for anyone who knows one
+        /// (only creator or poll admin, before any vote is cast)
         #[ink(message)]
-        pub fn has_poll_ended(&self, poll_id: u128) -> Result<bool, Error> {
+        pub fn set_emit_nullifier_in_events(&mut self, poll_id: u128, emit: bool) -> Result<(), Error> {
             let poll = self.polls.get(poll_id).ok_or(Error::PollNotFound)?;
-            let now = self.env().block_timestamp();
-            Ok(poll.ends_at > 0 && now > poll.ends_at)
+            if !self.can_manage_poll(&poll, self.env().caller()) {
+                return Err(Error::OnlyCreator);
+            }
+            if poll.total_votes > 0 {
+                return Err(Error::PollHasVotes);
+            }
+            if emit {
+                self.hidden_event_nullifiers.remove(poll_id);
+            } else {
+                self.hidden_event_nullifiers.insert(poll_id, &true);
+            }
+            Ok(())
         }
-    }
 
-    #[cfg(test)]
-    mod tests {
-        use super::*;
+        /// Check whether a poll's vote events carry the voter's nullifier
+        #[ink(message)]
+        pub fn emits_nullifier_in_events(&self, poll_id: u128) -> bool {
+            !self.hidden_event_nullifiers.contains(poll_id)
+        }
 
-        #[ink::test]
-        fn test_new_contract() {
-            let accounts = ink::env::test::default_accounts();
-            let contract = AnonymousPoll::new(accounts.bob);
-            
-            assert_eq!(contract.get_verifier_address(), accounts.bob);
-            assert_eq!(contract.get_owner(), accounts.alice);
-            assert_eq!(contract.get_total_polls(), 0);
+        /// Get the number of votes tallied for a poll in the given block.
+        /// Always 0 unless per-block counts were enabled with `set_block_vote_counts`.
+        #[ink(message)]
+        pub fn get_votes_in_block(&self, poll_id: u128, block: BlockNumber) -> u32 {
+            self.votes_in_block.get((poll_id, block)).unwrap_or(0)
         }
 
-        #[ink::test]
-        fn test_create_poll() {
-            let accounts = ink::env::test::default_accounts();
-            let mut contract = AnonymousPoll::new(accounts.bob);
+        /// Publish the current tallies as an intermediate checkpoint (only creator).
+        /// Returns the checkpoint's index.
+        #[ink(message)]
+        pub fn publish_checkpoint(&mut self, poll_id: u128) -> Result<u32, Error> {
+            let poll = self.polls.get(poll_id).ok_or(Error::PollNotFound)?;
+            if self.env().caller() != poll.creator {
+                return Err(Error::OnlyCreator);
+            }
 
-            let merkle_root = [1u8; 32];
-            let result = contract.create_poll(
-                String::from("Test Poll"),
-                String::from("This is a test poll"),
-                merkle_root,
-                3,
-                86400, // 1 day
-            );
+            let index = self.checkpoint_count.get(poll_id).unwrap_or(0);
+            self.checkpoints.insert((poll_id, index), &self.tallies(&poll));
+            self.checkpoint_count.insert(poll_id, &(index + 1));
 
-            assert!(result.is_ok());
-            let poll_id = result.unwrap();
-            assert_eq!(poll_id, 1);
+            self.env().emit_event(CheckpointPublished {
+                poll_id,
+                index,
+                block: self.env().block_number(),
+            });
 
-            let (exists, id, title, _, _, max_options, _, is_active, _, _, _) = contract.get_poll(poll_id);
-            assert!(exists);
-            assert_eq!(id, 1);
-            assert_eq!(title, "Test Poll");
-            assert_eq!(max_options, 3);
-            assert_eq!(is_active, true);
+            Ok(index)
         }
 
-        #[ink::test]
-        fn test_invalid_poll_parameters() {
-            let accounts = ink::env::test::default_accounts();
-            let mut contract = AnonymousPoll::new(accounts.bob);
+        /// Get the tallies published at a poll's checkpoint
+        #[ink(message)]
+        pub fn get_checkpoint(&self, poll_id: u128, index: u32) -> Option<Vec<u64>> {
+            self.checkpoints.get((poll_id, index))
+        }
 
-            // Test with 0 options
-            let result = contract.create_poll(
-                String::from("Invalid Poll"),
-                String::from("This poll has no options"),
-                [1u8; 32],
-                0,
-                0,
-            );
-            assert!(result.is_err());
+        /// Offer a poll to a new creator, who takes it over with `accept_poll_transfer`
+        /// (only current creator). A new offer replaces a pending one.
+        #[ink(message)]
+        pub fn transfer_poll(&mut self, poll_id: u128, new_creator: H160) -> Result<(), Error> {
+            let poll = self.polls.get(poll_id).ok_or(Error::PollNotFound)?;
+            if self.env().caller() != poll.creator {
+                return Err(Error::OnlyCreator);
+            }
 
-            // Test with invalid merkle root
-            let result = contract.create_poll(
-                String::from("Invalid Poll"),
-                String::from("This poll has invalid merkle root"),
-                [0u8; 32],
-                3,
-                0,
-            );
-            assert!(result.is_err());
+            self.pending_transfers.insert(poll_id, &new_creator);
+            Ok(())
         }
 
-        #[ink::test]
-        fn test_get_vote_tallies() {
-            let accounts = ink::env::test::default_accounts();
-            let mut contract = AnonymousPoll::new(accounts.bob);
-
-            let poll_id = contract.create_poll(
+        /// Take over a poll offered to the caller with `transfer_poll`.
+        /// Requiring consent stops anyone growing another account's poll index.
+        #[ink(message)]
+        pub fn accept_poll_transfer(&mut self, poll_id: u128) -> Result<(), Error> {
+            let mut poll = self.polls.get(poll_id).ok_or(Error::PollNotFound)?;
+            let new_creator = self.env().caller();
+            if self.pending_transfers.get(poll_id) != Some(new_creator) {
+                return Err(Error::NoPendingTransfer);
+            }
+            self.pending_transfers.remove(poll_id);
+            let from = poll.creator;
+
+            let mut from_polls = self.polls_by_creator.get(from).unwrap_or_default();
+            from_polls.retain(|id| *id != poll_id);
+            self.polls_by_creator.insert(from, &from_polls);
+
+            let mut to_polls = self.polls_by_creator.get(new_creator).unwrap_or_default();
+            to_polls.push(poll_id);
+            self.polls_by_creator.insert(new_creator, &to_polls);
+
+            poll.creator = new_creator;
+            self.polls.insert(poll_id, &poll);
+
+            // Co-admins and early viewers were chosen by the previous creator
+            let epoch = self.grant_epoch(poll_id).wrapping_add(1);
+            self.grant_epochs.insert(poll_id, &epoch);
+
+            self.env().emit_event(PollTransferred {
+                poll_id,
+                from,
+                to: new_creator,
+            });
+
+            Ok(())
+        }
+
+        /// Get the account a poll has been offered to, if any
+        #[ink(message)]
+        pub fn get_pending_transfer(&self, poll_id: u128) -> Option<H160> {
+            self.pending_transfers.get(poll_id)
+        }
+
+        /// Pause or resume voting on a single poll (only creator or poll admin)
+        #[ink(message)]
+        pub fn set_poll_paused(&mut self, poll_id: u128, paused: bool) -> Result<(), Error> {
+            let poll = self.polls.get(poll_id).ok_or(Error::PollNotFound)?;
+            if !self.can_manage_poll(&poll, self.env().caller()) {
+                return Err(Error::OnlyCreator);
+            }
+            if paused {
+                self.paused_polls.insert(poll_id, &true);
+            } else {
+                self.paused_polls.remove(poll_id);
+            }
+            Ok(())
+        }
+
+        /// Check if voting on a poll is paused by its creator
+        #[ink(message)]
+        pub fn is_poll_paused(&self, poll_id: u128) -> bool {
+            self.paused_polls.contains(poll_id)
+        }
+
+        /// Revoke the eligibility of a voter found ineligible after creation, identified by
+        /// their nullifier for this poll (only creator or poll admin). Votes already cast stay counted.
+        #[ink(message)]
+        pub fn revoke_nullifier(&mut self, poll_id: u128, nullifier: [u8; 32]) -> Result<(), Error> {
+            let poll = self.polls.get(poll_id).ok_or(Error::PollNotFound)?;
+            if !self.can_manage_poll(&poll, self.env().caller()) {
+                return Err(Error::OnlyCreator);
+            }
+            self.revoked_nullifiers.insert((poll_id, nullifier), &true);
+            Ok(())
+        }
+
+        /// Check if a nullifier's eligibility was revoked
+        #[ink(message)]
+        pub fn is_nullifier_revoked(&self, poll_id: u128, nullifier: [u8; 32]) -> bool {
+            self.revoked_nullifiers.contains((poll_id, nullifier))
+        }
+
+        /// Delegate management of a poll to a co-admin (only creator)
+        #[ink(message)]
+        pub fn add_poll_admin(&mut self, poll_id: u128, who: H160) -> Result<(), Error> {
+            let poll = self.polls.get(poll_id).ok_or(Error::PollNotFound)?;
+            if self.env().caller() != poll.creator {
+                return Err(Error::OnlyCreator);
+            }
+            self.poll_admins.insert((poll_id, who), &self.grant_epoch(poll_id));
+            Ok(())
+        }
+
+        /// Remove a poll co-admin (only creator)
+        #[ink(message)]
+        pub fn remove_poll_admin(&mut self, poll_id: u128, who: H160) -> Result<(), Error> {
+            let poll = self.polls.get(poll_id).ok_or(Error::PollNotFound)?;
+            if self.env().caller() != poll.creator {
+                return Err(Error::OnlyCreator);
+            }
+            self.poll_admins.remove((poll_id, who));
+            Ok(())
+        }
+
+        /// Whether an account may manage a poll: its creator or a delegated co-admin
+        fn can_manage_poll(&self, poll: &Poll, who: H160) -> bool {
+            who == poll.creator || self.is_poll_admin(poll.id, who)
+        }
+
+        /// The poll's current grant epoch; admin and viewer grants only count in the epoch they were made
+        fn grant_epoch(&self, poll_id: u128) -> u32 {
+            self.grant_epochs.get(poll_id).unwrap_or(0)
+        }
+
+        /// Close every listed poll whose end time has passed (only owner).
+        /// Returns one result per poll id, in input order, so a keeper can tell
+        /// which polls were closed and why the others were skipped.
+        #[ink(message)]
+        pub fn finalize_expired(&mut self, poll_ids: Vec<u128>) -> Result<Vec<Result<(), Error>>, Error> {
+            if self.env().caller() != self.owner {
+                return Err(Error::OnlyOwner);
+            }
+
+            if poll_ids.len() > MAX_BATCH_SIZE {
+                return Err(Error::BatchTooLarge);
+            }
+
+            let results = poll_ids
+                .into_iter()
+                .map(|poll_id| {
+                    let poll = self.polls.get(poll_id).ok_or(Error::PollNotFound)?;
+
+                    if !poll.is_active {
+                        return Err(Error::PollNotActive);
+                    }
+
+                    // Closing during the grace window would turn away the late votes it allows
+                    if !self.is_expired(&poll) || self.in_grace_period(&poll) {
+                        return Err(Error::PollNotEnded);
+                    }
+
+                    self.close(poll);
+                    Ok(())
+                })
+                .collect();
+
+            Ok(results)
+        }
+
+        /// Delete an ended poll's live result rows once `RESULT_RETENTION_MS` has passed
+        /// since it ended (only owner). Results stay readable from the frozen snapshot.
+        #[ink(message)]
+        pub fn prune_poll(&mut self, poll_id: u128) -> Result<(), Error> {
+            if self.env().caller() != self.owner {
+                return Err(Error::OnlyOwner);
+            }
+
+            let poll = self.polls.get(poll_id).ok_or(Error::PollNotFound)?;
+            let ended_at = self.ended_at(&poll).ok_or(Error::PollNotEnded)?;
+
+            if self.env().block_timestamp() <= ended_at.saturating_add(RESULT_RETENTION_MS) {
+                return Err(Error::RetentionNotElapsed);
+            }
+
+            if !self.final_results.contains(poll_id) {
+                self.final_results.insert(poll_id, &self.tallies(&poll));
+            }
+
+            for option in 0..poll.max_options {
+                self.vote_tallies.remove((poll_id, option));
+            }
+
+            Ok(())
+        }
+
+        /// Mark a poll inactive, freeze its results and emit `PollClosed`
+        fn close(&mut self, mut poll: Poll) {
+            // Closing an already closed poll is a no-op: it must not count or record it
+            // twice, restart the retention clock or emit another `PollClosed`
+            if !poll.is_active {
+                return;
+            }
+
+            self.final_results.insert(poll.id, &self.tallies(&poll));
+            self.active_polls = self.active_polls.saturating_sub(1);
+            self.closed_at_block.insert(poll.id, &self.env().block_number());
+            self.closed_at.insert(poll.id, &self.env().block_timestamp());
+            poll.is_active = false;
+            self.polls.insert(poll.id, &poll);
+
+            self.env().emit_event(PollClosed {
+                poll_id: poll.id,
+                total_votes: poll.total_votes,
+            });
+        }
+
+        /// When the poll ended: the earlier of its scheduled end and its closing time
+        fn ended_at(&self, poll: &Poll) -> Option<u64> {
+            let scheduled = self.is_expired(poll).then_some(poll.ends_at);
+            let closed = self.closed_at.get(poll.id);
+
+            match (scheduled, closed) {
+                (Some(scheduled), Some(closed)) => Some(scheduled.min(closed)),
+                (scheduled, closed) => scheduled.or(closed),
+            }
+        }
+
+        /// Whether the poll's end time has passed
+        fn is_expired(&self, poll: &Poll) -> bool {
+            poll.ends_at > 0 && self.env().block_timestamp() > poll.ends_at
+        }
+
+        /// Whether the poll's end time has passed but its grace period has not
+        fn in_grace_period(&self, poll: &Poll) -> bool {
+            let grace = self.grace_periods.get(poll.id).unwrap_or(0);
+            self.is_expired(poll) && self.env().block_timestamp() <= poll.ends_at.saturating_add(grace)
+        }
+
+        /// Get poll details
+        #[ink(message)]
+        #[allow(clippy::type_complexity)]
+        pub fn get_poll(&self, poll_id: u128) -> (bool, u128, String, String, [u8; 32], u32, H160, bool, u64, u64, u64) {
+            match self.polls.get(poll_id) {
+                Some(poll) => (
+                    true,  // exists
+                    poll.id,
+                    poll.title,
+                    poll.description,
+                    poll.merkle_root,
+                    poll.max_options,
+                    poll.creator,
+                    poll.is_active,
+                    poll.total_votes,
+                    poll.created_at,
+                    poll.ends_at,
+                ),
+                None => (
+                    false,  // doesn't exist
+                    0,
+                    String::new(),
+                    String::new(),
+                    [0u8; 32],
+                    0,
+                    H160::from([0u8; 20]),
+                    false,
+                    0,
+                    0,
+                    0,
+                ),
+            }
+        }
+
+        /// Get several polls at once, in input order, with `None` for missing ids.
+        /// Fails with `BatchTooLarge` for more than `MAX_BATCH_SIZE` ids.
+        #[ink(message)]
+        pub fn get_polls(&self, poll_ids: Vec<u128>) -> Result<Vec<Option<Poll>>, Error> {
+            if poll_ids.len() > MAX_BATCH_SIZE {
+                return Err(Error::BatchTooLarge);
+            }
+
+            Ok(poll_ids
+                .into_iter()
+                .map(|poll_id| self.polls.get(poll_id))
+                .collect())
+        }
+
+        /// Get a poll as opaque SCALE-encoded bytes, for clients caching polls and
+        /// decoding them with the same codec
+        #[ink(message)]
+        pub fn get_poll_encoded(&self, poll_id: u128) -> Option<Vec<u8>> {
+            use ink::scale::Encode;
+            self.polls.get(poll_id).map(|poll| poll.encode())
+        }
+
+        /// Get the block a poll was actually closed in, whether early or after its
+        /// scheduled end. `None` until the poll is closed or finalized.
+        #[ink(message)]
+        pub fn get_ended_at(&self, poll_id: u128) -> Option<BlockNumber> {
+            self.closed_at_block.get(poll_id)
+        }
+
+        /// Get the ids of open polls whose end time falls within the next `within_ms`
+        /// milliseconds. Scans poll ids `start..start + limit`, with `limit` capped at
+        /// `MAX_BATCH_SIZE`; page through by advancing `start` by `limit`.
+        #[ink(message)]
+        pub fn get_polls_ending_soon(&self, within_ms: u64, start: u128, limit: u32) -> Vec<u128> {
+            let now = self.env().block_timestamp();
+            let window_end = now.saturating_add(within_ms);
+            let limit = (limit as usize).min(MAX_BATCH_SIZE) as u128;
+            let end = start.saturating_add(limit).min(self.next_poll_id);
+
+            (start.max(1)..end)
+                .filter(|poll_id| {
+                    self.polls.get(poll_id).is_some_and(|poll| {
+                        poll.is_active && poll.ends_at != 0 && poll.ends_at >= now && poll.ends_at <= window_end
+                    })
+                })
+                .collect()
+        }
+
+        /// Get the ids of the polls a creator owns
+        #[ink(message)]
+        pub fn get_polls_by_creator(&self, creator: H160) -> Vec<u128> {
+            self.polls_by_creator.get(creator).unwrap_or_default()
+        }
+
+        /// Get the total votes across a creator's polls.
+        /// Only the creator's first `MAX_BATCH_SIZE` polls are counted.
+        #[ink(message)]
+        pub fn get_creator_total_votes(&self, creator: H160) -> u64 {
+            self.get_polls_by_creator(creator)
+                .into_iter()
+                .take(MAX_BATCH_SIZE)
+                .filter_map(|poll_id| self.polls.get(poll_id))
+                .map(|poll| poll.total_votes)
+                .fold(0u64, u64::saturating_add)
+        }
+
+        /// Get only a poll's merkle root, e.g. for building a proof
+        #[ink(message)]
+        pub fn get_merkle_root(&self, poll_id: u128) -> Option<[u8; 32]> {
+            self.polls.get(poll_id).map(|poll| poll.merkle_root)
+        }
+
+        /// Get the value held in a poll's prize pool
+        #[ink(message)]
+        pub fn get_prize_pool(&self, poll_id: u128) -> Option<U256> {
+            self.polls.get(poll_id).map(|poll| poll.prize_pool)
+        }
+
+        /// Get the part of a poll's prize pool that has not been paid out yet
+        #[ink(message)]
+        pub fn get_unclaimed_prize(&self, poll_id: u128) -> Option<U256> {
+            let poll = self.polls.get(poll_id)?;
+            let claimed = self.prize_claimed.get(poll_id).unwrap_or_default();
+            Some(poll.prize_pool.saturating_sub(claimed))
+        }
+
+        /// Pay out part of an ended poll's prize pool (only creator)
+        #[ink(message)]
+        pub fn claim_prize(&mut self, poll_id: u128, to: H160, amount: U256) -> Result<(), Error> {
+            let poll = self.polls.get(poll_id).ok_or(Error::PollNotFound)?;
+            if self.env().caller() != poll.creator {
+                return Err(Error::OnlyCreator);
+            }
+            if self.ended_at(&poll).is_none() {
+                return Err(Error::PollNotEnded);
+            }
+
+            let claimed = self.prize_claimed.get(poll_id).unwrap_or_default();
+            let unclaimed = poll.prize_pool.saturating_sub(claimed);
+            if amount > unclaimed {
+                return Err(Error::InsufficientPrize);
+            }
+
+            // Record the claim before moving value out
+            self.prize_claimed.insert(poll_id, &(claimed + amount));
+            self.prize_reserved = self.prize_reserved.saturating_sub(amount);
+            self.env().transfer(to, amount).map_err(|_| Error::TransferFailed)
+        }
+
+        /// Get a poll's optional settings
+        #[ink(message)]
+        pub fn get_poll_settings(&self, poll_id: u128) -> Option<PollSettings> {
+            self.polls.get(poll_id).map(|poll| poll.settings)
+        }
+
+        /// Get a digest clients can check to confirm they are dealing with the expected poll:
+        /// keccak256 of the SCALE-encoded `(id, title, description, merkle_root, max_options,
+        /// creator, created_at, ends_at)`. Votes, settings and prize state are not included.
+        #[ink(message)]
+        pub fn get_poll_config_hash(&self, poll_id: u128) -> Option<[u8; 32]> {
+            use ink::scale::Encode;
+
+            let poll = self.polls.get(poll_id)?;
+            let config = (
+                poll.id,
+                poll.title,
+                poll.description,
+                poll.merkle_root,
+                poll.max_options,
+                poll.creator,
+                poll.created_at,
+                poll.ends_at,
+            );
+            Some(self.env().hash_bytes::<ink::env::hash::Keccak256>(&config.encode()))
+        }
+
+        /// Get a poll's number of options without reading the whole poll
+        #[ink(message)]
+        pub fn get_option_count(&self, poll_id: u128) -> Option<u32> {
+            self.polls.get(poll_id).map(|poll| poll.max_options)
+        }
+
+        /// Check whether an option index would be accepted as a vote choice for a poll,
+        /// so clients can pre-check before generating a proof. `None` if the poll does not exist.
+        #[ink(message)]
+        pub fn is_valid_option(&self, poll_id: u128, option_index: u32) -> Option<bool> {
+            self.polls.get(poll_id).map(|poll| option_index < poll.max_options)
+        }
+
+        /// Get how many more votes an option can take before its count would overflow.
+        /// `None` if the poll or option does not exist, or the results are embargoed from the caller.
+        #[ink(message)]
+        pub fn remaining_vote_capacity(&self, poll_id: u128, option: u32) -> Option<u64> {
+            let poll = self.polls.get(poll_id)?;
+            if !self.can_view_results(&poll) {
+                return None;
+            }
+            let count = *self.tallies(&poll).get(option as usize)?;
+            Some(u64::MAX - count)
+        }
+
+        /// Get what a client needs to pick its proving setup: the poll's proof system and
+        /// the verifier contract, whose embedded key its proofs are checked against
+        #[ink(message)]
+        pub fn get_poll_proof_info(&self, poll_id: u128) -> Option<(ProofSystem, H160)> {
+            self.polls
+                .get(poll_id)
+                .map(|poll| (poll.settings.proof_system, self.verifier_address))
+        }
+
+        /// Get vote tally for a specific option.
+        /// Reads as 0 while the poll's results are embargoed from the caller.
+        #[ink(message)]
+        pub fn get_vote_tally(&self, poll_id: u128, option: u32) -> u64 {
+            match self.polls.get(poll_id) {
+                Some(poll) if self.can_view_results(&poll) => {
+                    self.tallies(&poll).get(option as usize).copied().unwrap_or(0)
+                }
+                _ => 0,
+            }
+        }
+
+        /// Get all vote tallies for a poll
+        #[ink(message)]
+        pub fn get_all_tallies(&self, poll_id: u128) -> Result<Vec<u64>, Error> {
+            let poll = self.polls.get(poll_id).ok_or(Error::PollNotFound)?;
+
+            if !self.can_view_results(&poll) {
+                return Err(Error::ResultsEmbargoed);
+            }
+
+            Ok(self.tallies(&poll))
+        }
+
+        /// Check whether a poll's results are final, i.e. the poll has ended and its
+        /// results were frozen. Expired polls become final once closed or pruned.
+        #[ink(message)]
+        pub fn is_final(&self, poll_id: u128) -> Option<bool> {
+            let poll = self.polls.get(poll_id)?;
+            Some(self.ended_at(&poll).is_some() && self.final_results.contains(poll_id))
+        }
+
+        /// Per-option counts, read from the frozen snapshot once one exists
+        fn tallies(&self, poll: &Poll) -> Vec<u64> {
+            if let Some(final_results) = self.final_results.get(poll.id) {
+                return final_results;
+            }
+
+            let mut tallies = Vec::new();
+
+            for option in 0..poll.max_options {
+                let tally = self.vote_tallies.get((poll.id, option)).unwrap_or(0);
+                tallies.push(tally);
+            }
+
+            tallies
+        }
+
+        /// Get up to `max_samples` of an option's most recent (block, count) samples, oldest first.
+        /// Empty unless the poll tracks history; embargoed polls read as empty as well.
+        #[ink(message)]
+        pub fn get_option_history(&self, poll_id: u128, option: u32, max_samples: u32) -> Vec<(BlockNumber, u64)> {
+            match self.polls.get(poll_id) {
+                Some(poll) if self.can_view_results(&poll) => {
+                    let history = self.option_history.get((poll_id, option)).unwrap_or_default();
+                    let skip = history.len().saturating_sub(max_samples as usize);
+                    history[skip..].to_vec()
+                }
+                _ => Vec::new(),
+            }
+        }
+
+        /// Get each option's count together with its share of all votes in basis points,
+        /// rounded according to `rounding`
+        #[ink(message)]
+        pub fn get_results_with_percentages(&self, poll_id: u128, rounding: RoundingMode) -> Option<Vec<(u64, u32)>> {
+            let tallies = self.get_all_tallies(poll_id).ok()?;
+            let shares = match rounding {
+                RoundingMode::Floor => {
+                    let total = tallies.iter().copied().fold(0u64, u64::saturating_add);
+                    tallies.iter().map(|count| bps(*count, total)).collect()
+                }
+                RoundingMode::LargestRemainder => largest_remainder_bps(&tallies),
+            };
+            Some(tallies.into_iter().zip(shares).collect())
+        }
+
+        /// Get a bitset of the options that received votes: bit `i % 8` of byte `i / 8`
+        /// is set when option `i` has a nonzero count
+        #[ink(message)]
+        pub fn get_voted_options_bitmap(&self, poll_id: u128) -> Option<Vec<u8>> {
+            let tallies = self.get_all_tallies(poll_id).ok()?;
+            let mut bitmap = ink::prelude::vec![0u8; tallies.len().div_ceil(8)];
+            for (option, count) in tallies.iter().enumerate() {
+                if *count > 0 {
+                    bitmap[option / 8] |= 1 << (option % 8);
+                }
+            }
+            Some(bitmap)
+        }
+
+        /// Get how far the leading option is ahead of the runner-up (0 on a tie, the
+        /// leader's count for a single-option poll)
+        #[ink(message)]
+        pub fn get_margin(&self, poll_id: u128) -> Option<u64> {
+            let mut tallies = self.get_all_tallies(poll_id).ok()?;
+            tallies.sort_unstable_by(|a, b| b.cmp(a));
+            let winner = tallies.first().copied().unwrap_or(0);
+            let runner_up = tallies.get(1).copied().unwrap_or(0);
+            Some(winner - runner_up)
+        }
+
+        /// Get the lowest and highest option counts of a poll, showing how lopsided it is
+        #[ink(message)]
+        pub fn get_vote_spread(&self, poll_id: u128) -> Option<(u64, u64)> {
+            let tallies = self.get_all_tallies(poll_id).ok()?;
+            let min = tallies.iter().copied().min().unwrap_or(0);
+            let max = tallies.iter().copied().max().unwrap_or(0);
+            Some((min, max))
+        }
+
+        /// Let an account read a poll's embargoed results early (only creator or poll admin)
+        #[ink(message)]
+        pub fn grant_results_access(&mut self, poll_id: u128, who: H160) -> Result<(), Error> {
+            let poll = self.polls.get(poll_id).ok_or(Error::PollNotFound)?;
+            if !self.can_manage_poll(&poll, self.env().caller()) {
+                return Err(Error::OnlyCreator);
+            }
+            self.results_viewers.insert((poll_id, who), &self.grant_epoch(poll_id));
+            Ok(())
+        }
+
+        /// Withdraw an account's early access to a poll's results (only creator or poll admin)
+        #[ink(message)]
+        pub fn revoke_results_access(&mut self, poll_id: u128, who: H160) -> Result<(), Error> {
+            let poll = self.polls.get(poll_id).ok_or(Error::PollNotFound)?;
+            if !self.can_manage_poll(&poll, self.env().caller()) {
+                return Err(Error::OnlyCreator);
+            }
+            self.results_viewers.remove((poll_id, who));
+            Ok(())
+        }
+
+        /// Whether the caller may read the poll's results.
+        /// Embargoed results are visible to the creator, the owner and granted
+        /// viewers while the poll runs, and to everyone once it is over.
+        fn can_view_results(&self, poll: &Poll) -> bool {
+            if !poll.settings.embargo_results || !poll.is_active || self.is_expired(poll) {
+                return true;
+            }
+
+            let caller = self.env().caller();
+            self.can_manage_poll(poll, caller)
+                || caller == self.owner
+                || self.results_viewers.get((poll.id, caller)) == Some(self.grant_epoch(poll.id))
+        }
+
+        /// Get several polls' tallies at once, in input order, with `None` for missing
+        /// ids and results embargoed from the caller.
+        /// Fails with `BatchTooLarge` for more than `MAX_BATCH_SIZE` ids.
+        #[ink(message)]
+        pub fn get_results_batch(&self, poll_ids: Vec<u128>) -> Result<Vec<Option<Vec<u64>>>, Error> {
+            if poll_ids.len() > MAX_BATCH_SIZE {
+                return Err(Error::BatchTooLarge);
+            }
+
+            Ok(poll_ids
+                .into_iter()
+                .map(|poll_id| self.get_all_tallies(poll_id).ok())
+                .collect())
+        }
+
+        /// Get the tallies of several polls, e.g. the rounds of a multi-round election.
+        /// Nonexistent poll ids are skipped. Fails with `BatchTooLarge` for more than `MAX_BATCH_SIZE` ids.
+        #[ink(message)]
+        pub fn get_series_tally(&self, poll_ids: Vec<u128>) -> Result<Vec<(u128, Vec<u64>)>, Error> {
+            if poll_ids.len() > MAX_BATCH_SIZE {
+                return Err(Error::BatchTooLarge);
+            }
+
+            Ok(poll_ids
+                .into_iter()
+                .filter_map(|poll_id| {
+                    self.get_all_tallies(poll_id)
+                        .ok()
+                        .map(|tallies| (poll_id, tallies))
+                })
+                .collect())
+        }
+
+        /// Check whether the leading option can no longer be overtaken, i.e. its count
+        /// exceeds all other votes plus every vote still outstanding.
+        /// Returns `None` if the poll doesn't exist or its eligible count is unknown.
+        #[ink(message)]
+        pub fn is_result_decided(&self, poll_id: u128) -> Option<bool> {
+            let poll = self.polls.get(poll_id)?;
+            if poll.settings.eligible_count == 0 {
+                return None;
+            }
+
+            let tallies = self.get_all_tallies(poll_id).ok()?;
+            let leading = tallies.iter().copied().max().unwrap_or(0);
+            let others = tallies.iter().sum::<u64>() - leading;
+            let remaining = u64::from(poll.settings.eligible_count).saturating_sub(poll.total_votes);
+
+            Some(leading > others.saturating_add(remaining))
+        }
+
+        /// Check whether a poll's votes meet its quorum right now, ended or not.
+        /// Polls without a quorum always meet it.
+        #[ink(message)]
+        pub fn meets_quorum(&self, poll_id: u128) -> Option<bool> {
+            let poll = self.polls.get(poll_id)?;
+            Some(poll.total_votes >= poll.settings.quorum)
+        }
+
+        /// Evaluate an ended poll against its quorum and spread rules
+        #[ink(message)]
+        pub fn get_poll_outcome(&self, poll_id: u128) -> Option<PollOutcome> {
+            let poll = self.polls.get(poll_id)?;
+
+            if poll.is_active && !self.is_expired(&poll) {
+                return Some(PollOutcome::Pending);
+            }
+
+            if poll.total_votes < poll.settings.quorum {
+                return Some(PollOutcome::FailedQuorum);
+            }
+
+            let options_voted = self.tallies(&poll).iter().filter(|count| **count > 0).count();
+            if options_voted < poll.settings.min_options_voted as usize {
+                return Some(PollOutcome::FailedSpread);
+            }
+
+            Some(PollOutcome::Valid)
+        }
+
+        /// Check if a nullifier has been used
+        #[ink(message)]
+        pub fn is_nullifier_used(&self, poll_id: u128, nullifier: [u8; 32]) -> bool {
+            self.used_nullifiers.contains((poll_id, nullifier))
+        }
+
+        /// Get the root of a poll's nullifier accumulator: a keccak256 Merkle tree of depth
+        /// `NULLIFIER_TREE_DEPTH` with used nullifiers as leaves in voting order and zero-filled
+        /// empty leaves. Auditors can prove inclusion or exclusion against it off-chain.
+        #[ink(message)]
+        pub fn get_nullifier_accumulator_root(&self, poll_id: u128) -> Option<[u8; 32]> {
+            if !self.polls.contains(poll_id) {
+                return None;
+            }
+            let count = self.nullifier_leaf_count.get(poll_id).unwrap_or(0);
+
+            let mut node = [0u8; 32];
+            let mut zero = [0u8; 32];
+            let mut size = count;
+            for level in 0..NULLIFIER_TREE_DEPTH as u8 {
+                node = if size & 1 == 1 {
+                    let sibling = self.nullifier_frontier.get((poll_id, level)).unwrap_or_default();
+                    self.hash_pair(&sibling, &node)
+                } else {
+                    self.hash_pair(&node, &zero)
+                };
+                zero = self.hash_pair(&zero, &zero);
+                size >>= 1;
+            }
+            Some(node)
+        }
+
+        /// Get the option a nullifier voted for, `None` if it has not voted.
+        /// Fails with `ChoiceNotStored` unless the poll stores choices, and with
+        /// `ResultsEmbargoed` while the results are hidden from the caller, since
+        /// choices of nullifiers seen in `VoteCast` would rebuild the tally.
+        #[ink(message)]
+        pub fn get_recorded_choice(&self, poll_id: u128, nullifier: [u8; 32]) -> Result<Option<u32>, Error> {
+            let poll = self.polls.get(poll_id).ok_or(Error::PollNotFound)?;
+            if !poll.settings.store_choices {
+                return Err(Error::ChoiceNotStored);
+            }
+            if !self.can_view_results(&poll) {
+                return Err(Error::ResultsEmbargoed);
+            }
+            Ok(self.vote_choices.get((poll_id, nullifier)))
+        }
+
+        /// Get everything a UI needs about a nullifier in one call: whether it could
+        /// vote now, whether it already voted and the option it chose. The choice is
+        /// left out while the poll's results are embargoed from the caller.
+        #[ink(message)]
+        pub fn get_voter_status(&self, poll_id: u128, nullifier: [u8; 32]) -> Option<VoterStatus> {
+            let poll = self.polls.get(poll_id)?;
+            let recorded_choice = if self.can_view_results(&poll) {
+                self.vote_choices.get((poll_id, nullifier))
+            } else {
+                None
+            };
+            Some(VoterStatus {
+                // Option 0 always exists, so this only runs the poll and nullifier checks
+                can_vote: self.validate_vote(poll_id, nullifier, 0).is_ok(),
+                nullifier_used: self.used_nullifiers.contains((poll_id, nullifier)),
+                has_recorded_choice: recorded_choice.is_some(),
+                recorded_choice: recorded_choice.unwrap_or(0),
+            })
+        }
+
+        /// Get whether a nullifier has been used and the block its vote was recorded in.
+        /// The block is 0 for an unused nullifier.
+        #[ink(message)]
+        pub fn nullifier_status(&self, poll_id: u128, nullifier: [u8; 32]) -> (bool, BlockNumber) {
+            match self.used_nullifiers.get((poll_id, nullifier)) {
+                Some(block) => (true, block),
+                None => (false, 0),
+            }
+        }
+
+        /// Check if an account is a delegated co-admin of a poll
+        #[ink(message)]
+        pub fn is_poll_admin(&self, poll_id: u128, who: H160) -> bool {
+            self.poll_admins.get((poll_id, who)) == Some(self.grant_epoch(poll_id))
+        }
+
+        /// Check if an address has voted in a non-anonymous poll.
+        /// Always false for anonymous polls, which never record voters.
+        #[ink(message)]
+        pub fn has_address_voted(&self, poll_id: u128, who: H160) -> bool {
+            self.voted.get((poll_id, who)).unwrap_or(false)
+        }
+
+        /// Get the verifier contract address
+        #[ink(message)]
+        pub fn get_verifier_address(&self) -> H160 {
+            self.verifier_address
+        }
+
+        /// Update verifier address (only owner)
+        #[ink(message)]
+        pub fn update_verifier(&mut self, new_verifier: H160) -> Result<(), Error> {
+            if self.env().caller() != self.owner {
+                return Err(Error::OnlyOwner);
+            }
+            self.verifier_address = new_verifier;
+            self.env().emit_event(VerifierUpdated {
+                updated_by: self.env().caller(),
+                verifier: new_verifier,
+            });
+            Ok(())
+        }
+
+        /// Replace the contract's code, keeping its storage (only owner)
+        #[ink(message)]
+        pub fn upgrade(&mut self, code_hash: [u8; 32]) -> Result<(), Error> {
+            if self.env().caller() != self.owner {
+                return Err(Error::OnlyOwner);
+            }
+            self.env()
+                .set_code_hash(&ink::H256::from(code_hash))
+                .map_err(|_| Error::UpgradeFailed)?;
+            self.env().emit_event(ContractUpgraded { code_hash });
+            Ok(())
+        }
+
+        /// Set the proof systems creators may select (only owner).
+        /// Applied through `set_circuit_config`, so it emits `CircuitConfigUpdated`.
+        #[ink(message)]
+        pub fn set_allowed_proof_systems(&mut self, proof_systems: Vec<ProofSystem>) -> Result<(), Error> {
+            let mut config = self.get_circuit_config();
+            config.allowed_proof_systems = proof_systems;
+            self.set_circuit_config(config)
+        }
+
+        /// Get the proof systems creators may select
+        #[ink(message)]
+        pub fn get_allowed_proof_systems(&self) -> Vec<ProofSystem> {
+            self.allowed_proof_systems.clone()
+        }
+
+        /// Get the domain separator bound into every proof's public inputs
+        #[ink(message)]
+        pub fn get_domain_separator(&self) -> [u8; 32] {
+            self.domain_separator
+        }
+
+        /// Get the contract-wide configuration in one call
+        #[ink(message)]
+        pub fn get_contract_info(&self) -> ContractInfo {
+            ContractInfo {
+                owner: self.owner,
+                paused: self.paused,
+                verifier_address: self.verifier_address,
+                next_poll_id: self.next_poll_id,
+                total_polls: self.get_total_polls(),
+                active_polls: self.active_polls,
+                distinct_creators: self.distinct_creators,
+            }
+        }
+
+        /// Pause or resume voting on every poll (only owner)
+        #[ink(message)]
+        pub fn set_paused(&mut self, paused: bool) -> Result<(), Error> {
+            if self.env().caller() != self.owner {
+                return Err(Error::OnlyOwner);
+            }
+            self.paused = paused;
+            Ok(())
+        }
+
+        /// Configure the circuit's option count and whether new polls must match it (only owner).
+        /// Applied through `set_circuit_config`, so it emits `CircuitConfigUpdated`.
+        #[ink(message)]
+        pub fn set_circuit_options(&mut self, circuit_max_options: u32, require_exact_options: bool) -> Result<(), Error> {
+            let mut config = self.get_circuit_config();
+            config.max_options = circuit_max_options;
+            config.require_exact_options = require_exact_options;
+            self.set_circuit_config(config)
+        }
+
+        /// Replace the whole circuit configuration in one call, so it is never seen
+        /// half-updated (only owner). Nothing is applied if any field is invalid.
+        #[ink(message)]
+        pub fn set_circuit_config(&mut self, config: CircuitConfig) -> Result<(), Error> {
+            if self.env().caller() != self.owner {
+                return Err(Error::OnlyOwner);
+            }
+            if config.require_exact_options && (config.max_options == 0 || config.max_options > 100) {
+                return Err(Error::InvalidPollParameters);
+            }
+            if config.max_proof_bytes == 0 || config.max_proof_bytes as usize > MAX_PROOF_SIZE {
+                return Err(Error::InvalidPollParameters);
+            }
+            if config.min_proof_bytes > config.max_proof_bytes {
+                return Err(Error::InvalidPollParameters);
+            }
+            // With no proof system allowed, no poll could ever be created
+            if config.allowed_proof_systems.is_empty() {
+                return Err(Error::InvalidPollParameters);
+            }
+
+            self.circuit_max_options = config.max_options;
+            self.require_exact_options = config.require_exact_options;
+            self.max_proof_bytes = config.max_proof_bytes;
+            self.min_proof_bytes = config.min_proof_bytes;
+            self.allowed_proof_systems = config.allowed_proof_systems.clone();
+
+            self.env().emit_event(CircuitConfigUpdated { config });
+            Ok(())
+        }
+
+        /// Get the current circuit configuration
+        #[ink(message)]
+        pub fn get_circuit_config(&self) -> CircuitConfig {
+            CircuitConfig {
+                max_options: self.circuit_max_options,
+                require_exact_options: self.require_exact_options,
+                max_proof_bytes: self.max_proof_bytes,
+                min_proof_bytes: self.min_proof_bytes,
+                allowed_proof_systems: self.allowed_proof_systems.clone(),
+            }
+        }
+
+        /// Get the caps applied to batch messages, proofs and history tracking
+        #[ink(message)]
+        pub fn get_limits(&self) -> Limits {
+            Limits {
+                max_batch_size: MAX_BATCH_SIZE as u32,
+                max_proof_size: MAX_PROOF_SIZE as u32,
+                max_history_samples: MAX_HISTORY_SAMPLES as u32,
+            }
+        }
+
+        /// Get the circuit's option count and whether new polls must match it
+        #[ink(message)]
+        pub fn get_circuit_options(&self) -> (u32, bool) {
+            (self.circuit_max_options, self.require_exact_options)
+        }
+
+        /// Recover funds sent to the contract by mistake (only owner).
+        /// Value reserved for unclaimed prize pools cannot be withdrawn.
+        #[ink(message)]
+        pub fn withdraw(&mut self, to: H160, amount: U256) -> Result<(), Error> {
+            if self.env().caller() != self.owner {
+                return Err(Error::OnlyOwner);
+            }
+            let available = self.env().balance().saturating_sub(self.prize_reserved);
+            if amount > available {
+                return Err(Error::InsufficientBalance);
+            }
+            self.env().transfer(to, amount).map_err(|_| Error::TransferFailed)
+        }
+
+        /// Check if voting is globally paused
+        #[ink(message)]
+        pub fn is_paused(&self) -> bool {
+            self.paused
+        }
+
+        /// Get the chain id bound into every proof's public inputs
+        #[ink(message)]
+        pub fn get_chain_id(&self) -> u32 {
+            self.chain_id
+        }
+
+        /// Get the BN254 scalar field modulus as a decimal string, so clients reduce
+        /// values into the same field the contract and circuit use
+        #[ink(message)]
+        pub fn get_field_modulus(&self) -> String {
+            String::from(BN254_MODULUS)
+        }
+
+        /// Get the compressed proof layout version this contract reads
+        #[ink(message)]
+        pub fn proof_format_version(&self) -> u32 {
+            PROOF_FORMAT_VERSION
+        }
+
+        /// Get contract owner
+        #[ink(message)]
+        pub fn get_owner(&self) -> H160 {
+            self.owner
+        }
+
+        /// Get the number of polls that have not been closed yet.
+        /// Expired polls count as active until they are closed or finalized.
+        #[ink(message)]
+        pub fn active_poll_count(&self) -> u32 {
+            self.active_polls
+        }
+
+        /// Get total number of polls
+        #[ink(message)]
+        pub fn get_total_polls(&self) -> u128 {
+            self.next_poll_id - 1
+        }
+
+        /// Get the number of distinct addresses that have created a poll
+        #[ink(message)]
+        pub fn get_distinct_creator_count(&self) -> u32 {
+            self.distinct_creators
+        }
+
+        /// Check if poll has ended
+        #[ink(message)]
+        pub fn has_poll_ended(&self, poll_id: u128) -> Result<bool, Error> {
+            let poll = self.polls.get(poll_id).ok_or(Error::PollNotFound)?;
+            Ok(self.is_expired(&poll))
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        /// Merkle root shared by test polls: a valid field element distinct from test nullifiers
+        const TEST_ROOT: [u8; 32] = [0x2bu8; 32];
+
+        /// Create a two-option poll with no end time
+        fn create_test_poll(contract: &mut AnonymousPoll) -> u128 {
+            contract.create_poll(
+                String::from("Test Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap()
+        }
+
+        #[ink::test]
+        fn test_new_contract() {
+            let accounts = ink::env::test::default_accounts();
+            let contract = AnonymousPoll::new(accounts.bob);
+            
+            assert_eq!(contract.get_verifier_address(), accounts.bob);
+            assert_eq!(contract.get_owner(), accounts.alice);
+            assert_eq!(contract.get_total_polls(), 0);
+        }
+
+        #[ink::test]
+        fn test_deployer_becomes_owner() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.django);
+            let contract = AnonymousPoll::new(accounts.bob);
+            assert_eq!(contract.get_owner(), accounts.django);
+
+            // The deploy-time VerifierUpdated is attributed to the deployer
+            let events = ink::env::test::recorded_events();
+            assert_eq!(events.len(), 1);
+            let mut deployer_topic = [0u8; 32];
+            deployer_topic[..20].copy_from_slice(accounts.django.as_bytes());
+            assert_eq!(events[0].topics[1], deployer_topic);
+        }
+
+        #[ink::test]
+        fn test_create_poll() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let merkle_root = [1u8; 32];
+            let result = contract.create_poll(
+                String::from("Test Poll"),
+                String::from("This is a test poll"),
+                merkle_root,
+                3,
+                86400, // 1 day
+            );
+
+            assert!(result.is_ok());
+            let poll_id = result.unwrap();
+            assert_eq!(poll_id, 1);
+
+            let (exists, id, title, _, _, max_options, _, is_active, _, _, _) = contract.get_poll(poll_id);
+            assert!(exists);
+            assert_eq!(id, 1);
+            assert_eq!(title, "Test Poll");
+            assert_eq!(max_options, 3);
+            assert!(is_active);
+        }
+
+        #[ink::test]
+        fn test_invalid_poll_parameters() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            // Test with 0 options
+            let result = contract.create_poll(
+                String::from("Invalid Poll"),
+                String::from("This poll has no options"),
+                [1u8; 32],
+                0,
+                0,
+            );
+            assert!(result.is_err());
+
+            // Test with invalid merkle root
+            let result = contract.create_poll(
+                String::from("Invalid Poll"),
+                String::from("This poll has invalid merkle root"),
+                [0u8; 32],
+                3,
+                0,
+            );
+            assert!(result.is_err());
+        }
+
+        #[ink::test]
+        fn test_get_vote_tallies() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = contract.create_poll(
+                String::from("Test Poll"),
+                String::from("Test"),
+                [1u8; 32],
+                3,
+                0,
+            ).unwrap();
+
+            // All tallies should start at 0
+            let tallies = contract.get_all_tallies(poll_id).unwrap();
+            assert_eq!(tallies.len(), 3);
+            assert_eq!(tallies[0], 0);
+            assert_eq!(tallies[1], 0);
+            assert_eq!(tallies[2], 0);
+        }
+
+        #[ink::test]
+        fn test_nullifier_tracking() {
+            let accounts = ink::env::test::default_accounts();
+            let contract = AnonymousPoll::new(accounts.bob);
+
+            let nullifier = [42u8; 32];
+            let poll_id = 1;
+
+            // Nullifier should not be used initially
+            assert!(!contract.is_nullifier_used(poll_id, nullifier));
+        }
+
+        #[ink::test]
+        fn test_get_series_tally() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let round_one = contract.create_poll(
+                String::from("Round 1"),
+                String::from("First round"),
+                TEST_ROOT,
+                3,
+                0,
+            ).unwrap();
+            let round_two = contract.create_poll(
+                String::from("Round 2"),
+                String::from("Runoff"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+
+            contract.cast_vote(round_one, vec![1], [1u8; 32], 2).unwrap();
+            contract.cast_vote(round_one, vec![1], [2u8; 32], 2).unwrap();
+            contract.cast_vote(round_two, vec![1], [3u8; 32], 0).unwrap();
+
+            let series = contract.get_series_tally(vec![round_one, 99, round_two]).unwrap();
+            assert_eq!(series.len(), 2);
+            assert_eq!(series[0], (round_one, vec![0, 0, 2]));
+            assert_eq!(series[1], (round_two, vec![1, 0]));
+        }
+
+        #[ink::test]
+        fn test_get_series_tally_caps_input() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = create_test_poll(&mut contract);
+
+            let series = contract.get_series_tally(vec![poll_id; MAX_BATCH_SIZE]).unwrap();
+            assert_eq!(series.len(), MAX_BATCH_SIZE);
+            assert_eq!(
+                contract.get_series_tally(vec![poll_id; MAX_BATCH_SIZE + 10]),
+                Err(Error::BatchTooLarge)
+            );
+        }
+
+        #[ink::test]
+        fn test_finalize_expired() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let expired = contract.create_poll(
+                String::from("Short Poll"),
+                String::from("Ends after 10 seconds"),
+                TEST_ROOT,
+                2,
+                10,
+            ).unwrap();
+            let running = contract.create_poll(
+                String::from("Long Poll"),
+                String::from("Ends after a day"),
+                TEST_ROOT,
+                2,
+                86400,
+            ).unwrap();
+            let closed = contract.create_poll(
+                String::from("Closed Poll"),
+                String::from("Already closed"),
+                TEST_ROOT,
+                2,
+                10,
+            ).unwrap();
+            contract.close_poll(closed).unwrap();
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(20_000);
+
+            let results = contract
+                .finalize_expired(vec![expired, running, closed, 99])
+                .unwrap();
+            assert_eq!(
+                results,
+                vec![
+                    Ok(()),
+                    Err(Error::PollNotEnded),
+                    Err(Error::PollNotActive),
+                    Err(Error::PollNotFound),
+                ]
+            );
+
+            let (_, _, _, _, _, _, _, is_active, _, _, _) = contract.get_poll(expired);
+            assert!(!is_active);
+            let (_, _, _, _, _, _, _, is_active, _, _, _) = contract.get_poll(running);
+            assert!(is_active);
+        }
+
+        #[ink::test]
+        fn test_finalize_expired_only_owner() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            ink::env::test::set_caller(accounts.charlie);
+            assert_eq!(contract.finalize_expired(vec![1]), Err(Error::OnlyOwner));
+        }
+
+        /// Run-length encode a proof in the format read by `decompress_proof`
+        fn compress_proof(proof: &[u8]) -> Vec<u8> {
+            let mut compressed = vec![PROOF_FORMAT_VERSION as u8];
+            compressed.extend_from_slice(&(proof.len() as u32).to_be_bytes());
+            for run in proof.chunk_by(|a, b| a == b) {
+                for chunk in run.chunks(u8::MAX as usize) {
+                    compressed.push(chunk.len() as u8);
+                    compressed.push(chunk[0]);
+                }
+            }
+            compressed
+        }
+
+        #[ink::test]
+        fn test_decompress_proof_round_trip() {
+            let mut proof = vec![1u8];
+            proof.extend_from_slice(&[0u8; 600]);
+            proof.extend_from_slice(&[7, 7, 9]);
+
+            let compressed = compress_proof(&proof);
+            assert!(compressed.len() < proof.len());
+            assert_eq!(decompress_proof(&compressed), Ok(proof));
+        }
+
+        #[ink::test]
+        fn test_decompress_proof_rejects_malformed_input() {
+            let v = PROOF_FORMAT_VERSION as u8;
+            // Missing version byte
+            assert_eq!(decompress_proof(&[]), Err(Error::DecompressionFailed));
+            // Too short for the length header
+            assert_eq!(decompress_proof(&[v, 0, 0]), Err(Error::DecompressionFailed));
+            // Zero-length run
+            assert_eq!(decompress_proof(&[v, 0, 0, 0, 1, 0, 5]), Err(Error::DecompressionFailed));
+            // Runs overflow the declared length
+            assert_eq!(decompress_proof(&[v, 0, 0, 0, 1, 2, 5]), Err(Error::DecompressionFailed));
+            // Runs fall short of the declared length
+            assert_eq!(decompress_proof(&[v, 0, 0, 0, 3, 2, 5]), Err(Error::DecompressionFailed));
+            // Declared length above the cap
+            assert_eq!(decompress_proof(&[v, 0, 1, 0, 0]), Err(Error::DecompressionFailed));
+        }
+
+        #[ink::test]
+        fn test_cast_vote_compressed() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = create_test_poll(&mut contract);
+
+            let mut proof = vec![1u8];
+            proof.extend_from_slice(&[0u8; 64]);
+
+            contract.cast_vote(poll_id, proof.clone(), [1u8; 32], 0).unwrap();
+            contract
+                .cast_vote_compressed(poll_id, compress_proof(&proof), [2u8; 32], 0)
+                .unwrap();
+            assert_eq!(contract.get_vote_tally(poll_id, 0), 2);
+
+            assert_eq!(
+                contract.cast_vote_compressed(poll_id, vec![PROOF_FORMAT_VERSION as u8, 0, 0, 0, 1], [3u8; 32], 0),
+                Err(Error::DecompressionFailed)
+            );
+        }
+
+        #[ink::test]
+        fn test_anonymous_poll_does_not_record_voter() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = create_test_poll(&mut contract);
+            assert!(contract.get_poll_settings(poll_id).unwrap().anonymous);
+
+            ink::env::test::set_caller(accounts.charlie);
+            contract.cast_vote(poll_id, vec![1], [1u8; 32], 0).unwrap();
+            assert!(!contract.has_address_voted(poll_id, accounts.charlie));
+        }
+
+        #[ink::test]
+        fn test_pseudonymous_poll_records_voter() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = create_test_poll(&mut contract);
+            contract
+                .update_poll_settings(poll_id, PollSettings { anonymous: false, ..Default::default() })
+                .unwrap();
+
+            ink::env::test::set_caller(accounts.charlie);
+            contract.cast_vote(poll_id, vec![1], [1u8; 32], 0).unwrap();
+            assert!(contract.has_address_voted(poll_id, accounts.charlie));
+            assert!(!contract.has_address_voted(poll_id, accounts.django));
+        }
+
+        #[ink::test]
+        fn test_update_poll_settings_restrictions() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = create_test_poll(&mut contract);
+
+            ink::env::test::set_caller(accounts.charlie);
+            assert_eq!(
+                contract.update_poll_settings(poll_id, PollSettings::default()),
+                Err(Error::OnlyCreator)
+            );
+            contract.cast_vote(poll_id, vec![1], [1u8; 32], 0).unwrap();
+
+            ink::env::test::set_caller(accounts.alice);
+            assert_eq!(
+                contract.update_poll_settings(poll_id, PollSettings::default()),
+                Err(Error::PollHasVotes)
+            );
+        }
+
+        #[ink::test]
+        fn test_is_result_decided() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = create_test_poll(&mut contract);
+
+            // Unknown electorate size
+            assert_eq!(contract.is_result_decided(poll_id), None);
+            assert_eq!(contract.is_result_decided(99), None);
+
+            contract
+                .update_poll_settings(poll_id, PollSettings { eligible_count: 5, ..Default::default() })
+                .unwrap();
+            assert_eq!(contract.is_result_decided(poll_id), Some(false));
+
+            // 2 votes with 3 outstanding: still undecided
+            contract.cast_vote(poll_id, vec![1], [1u8; 32], 0).unwrap();
+            contract.cast_vote(poll_id, vec![1], [2u8; 32], 0).unwrap();
+            assert_eq!(contract.is_result_decided(poll_id), Some(false));
+
+            // 3 votes against 0 with 2 outstanding: locked
+            contract.cast_vote(poll_id, vec![1], [3u8; 32], 0).unwrap();
+            assert_eq!(contract.is_result_decided(poll_id), Some(true));
+        }
+
+        #[ink::test]
+        fn test_allowed_proof_systems() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+            assert_eq!(contract.get_allowed_proof_systems(), vec![ProofSystem::UltraHonk]);
+
+            let poll_id = create_test_poll(&mut contract);
+
+            let plonk = PollSettings { proof_system: ProofSystem::UltraPlonk, ..Default::default() };
+            assert_eq!(
+                contract.update_poll_settings(poll_id, plonk.clone()),
+                Err(Error::ProofSystemNotAllowed)
+            );
+            assert_eq!(
+                contract.get_poll_settings(poll_id).unwrap().proof_system,
+                ProofSystem::UltraHonk
+            );
+
+            contract
+                .set_allowed_proof_systems(vec![ProofSystem::UltraHonk, ProofSystem::UltraPlonk])
+                .unwrap();
+            contract.update_poll_settings(poll_id, plonk).unwrap();
+            assert_eq!(
+                contract.get_poll_settings(poll_id).unwrap().proof_system,
+                ProofSystem::UltraPlonk
+            );
+
+            ink::env::test::set_caller(accounts.charlie);
+            assert_eq!(contract.set_allowed_proof_systems(vec![]), Err(Error::OnlyOwner));
+        }
+
+        #[ink::test]
+        fn test_domain_separator_in_public_inputs() {
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_callee(accounts.eve);
+            let first = AnonymousPoll::new(accounts.bob);
+            ink::env::test::set_callee(accounts.frank);
+            let second = AnonymousPoll::new(accounts.bob);
+
+            let separator = first.get_domain_separator();
+            assert_eq!(&separator[12..], accounts.eve.as_bytes());
+            assert_eq!(&separator[..12], &[0u8; 12]);
+            assert_ne!(separator, second.get_domain_separator());
+
+            let inputs = first.encode_public_inputs([1u8; 32], [2u8; 32], 1, 3);
+            assert_eq!(&inputs[inputs.len() - 36..inputs.len() - 4], &separator);
+            assert_ne!(inputs, second.encode_public_inputs([1u8; 32], [2u8; 32], 1, 3));
+        }
+
+        #[ink::test]
+        fn test_create_poll_with_prize_pool() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let unfunded = contract.create_poll(
+                String::from("Unfunded Poll"),
+                String::from("No prize"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+
+            ink::env::test::set_value_transferred(U256::from(1_000));
+            let funded = contract.create_poll(
+                String::from("Funded Poll"),
+                String::from("With prize"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+
+            assert_eq!(contract.get_prize_pool(unfunded), Some(U256::zero()));
+            assert_eq!(contract.get_prize_pool(funded), Some(U256::from(1_000)));
+            assert_eq!(contract.get_prize_pool(99), None);
+        }
+
+        #[ink::test]
+        fn test_results_access_for_embargoed_poll() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            ink::env::test::set_caller(accounts.charlie);
+            let poll_id = create_test_poll(&mut contract);
+            contract
+                .update_poll_settings(poll_id, PollSettings { embargo_results: true, ..Default::default() })
+                .unwrap();
+            contract.cast_vote(poll_id, vec![1], [1u8; 32], 1).unwrap();
+            contract.grant_results_access(poll_id, accounts.django).unwrap();
+
+            ink::env::test::set_caller(accounts.django);
+            assert_eq!(contract.get_all_tallies(poll_id), Ok(vec![0, 1]));
+            assert_eq!(contract.get_vote_tally(poll_id, 1), 1);
+
+            ink::env::test::set_caller(accounts.eve);
+            assert_eq!(contract.get_all_tallies(poll_id), Err(Error::ResultsEmbargoed));
+            assert_eq!(contract.get_vote_tally(poll_id, 1), 0);
+            assert_eq!(
+                contract.grant_results_access(poll_id, accounts.eve),
+                Err(Error::OnlyCreator)
+            );
+
+            ink::env::test::set_caller(accounts.charlie);
+            contract.revoke_results_access(poll_id, accounts.django).unwrap();
+
+            ink::env::test::set_caller(accounts.django);
+            assert_eq!(contract.get_all_tallies(poll_id), Err(Error::ResultsEmbargoed));
+
+            // Once the poll is closed the embargo lifts for everyone
+            ink::env::test::set_caller(accounts.charlie);
+            contract.close_poll(poll_id).unwrap();
+            ink::env::test::set_caller(accounts.eve);
+            assert_eq!(contract.get_all_tallies(poll_id), Ok(vec![0, 1]));
+        }
+
+        #[ink::test]
+        fn test_get_vote_spread() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = contract.create_poll(
+                String::from("Test Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                3,
+                0,
+            ).unwrap();
+            assert_eq!(contract.get_vote_spread(poll_id), Some((0, 0)));
+            assert_eq!(contract.get_vote_spread(99), None);
+
+            for i in 0..4u8 {
+                contract.cast_vote(poll_id, vec![1], [i + 1; 32], 0).unwrap();
+            }
+            contract.cast_vote(poll_id, vec![1], [10u8; 32], 2).unwrap();
+
+            assert_eq!(contract.get_vote_spread(poll_id), Some((0, 4)));
+        }
+
+        #[ink::test]
+        fn test_get_merkle_root() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = contract.create_poll(
+                String::from("Test Poll"),
+                String::from("Test"),
+                [7u8; 32],
+                2,
+                0,
+            ).unwrap();
+
+            let (_, _, _, _, merkle_root, _, _, _, _, _, _) = contract.get_poll(poll_id);
+            assert_eq!(contract.get_merkle_root(poll_id), Some(merkle_root));
+            assert_eq!(contract.get_merkle_root(99), None);
+        }
+
+        #[ink::test]
+        fn test_vote_counts_exceed_u32() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = create_test_poll(&mut contract);
+
+            // Seed counts at the old u32 ceiling
+            let start = u64::from(u32::MAX);
+            contract.vote_tallies.insert((poll_id, 0), &start);
+            let mut poll = contract.polls.get(poll_id).unwrap();
+            poll.total_votes = start;
+            contract.polls.insert(poll_id, &poll);
+
+            contract.cast_vote(poll_id, vec![1], [1u8; 32], 0).unwrap();
+            contract.cast_vote(poll_id, vec![1], [2u8; 32], 0).unwrap();
+
+            assert_eq!(contract.get_vote_tally(poll_id, 0), start + 2);
+            let (_, _, _, _, _, _, _, _, total_votes, _, _) = contract.get_poll(poll_id);
+            assert_eq!(total_votes, start + 2);
+        }
+
+        #[ink::test]
+        fn test_vote_count_overflow_is_rejected() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = create_test_poll(&mut contract);
+            contract.vote_tallies.insert((poll_id, 0), &u64::MAX);
+
+            assert_eq!(
+                contract.cast_vote(poll_id, vec![1], [1u8; 32], 0),
+                Err(Error::VoteCountOverflow)
+            );
+        }
+
+        #[ink::test]
+        fn test_nullifier_status_records_block() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = create_test_poll(&mut contract);
+            assert_eq!(contract.nullifier_status(poll_id, [1u8; 32]), (false, 0));
+
+            ink::env::test::set_block_number::<ink::env::DefaultEnvironment>(42);
+            contract.cast_vote(poll_id, vec![1], [1u8; 32], 0).unwrap();
+
+            ink::env::test::set_block_number::<ink::env::DefaultEnvironment>(50);
+            assert_eq!(contract.nullifier_status(poll_id, [1u8; 32]), (true, 42));
+            assert_eq!(contract.nullifier_status(poll_id, [2u8; 32]), (false, 0));
+            assert!(contract.is_nullifier_used(poll_id, [1u8; 32]));
+        }
+
+        #[ink::test]
+        fn test_prune_poll_keeps_results() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = create_test_poll(&mut contract);
+            contract.cast_vote(poll_id, vec![1], [1u8; 32], 1).unwrap();
+            assert_eq!(contract.prune_poll(poll_id), Err(Error::PollNotEnded));
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1_000);
+            contract.close_poll(poll_id).unwrap();
+            assert_eq!(contract.prune_poll(poll_id), Err(Error::RetentionNotElapsed));
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(
+                1_000 + RESULT_RETENTION_MS + 1,
+            );
+            ink::env::test::set_caller(accounts.charlie);
+            assert_eq!(contract.prune_poll(poll_id), Err(Error::OnlyOwner));
+
+            ink::env::test::set_caller(accounts.alice);
+            contract.prune_poll(poll_id).unwrap();
+
+            assert_eq!(contract.vote_tallies.get((poll_id, 1)), None);
+            assert_eq!(contract.get_all_tallies(poll_id), Ok(vec![0, 1]));
+            assert_eq!(contract.get_vote_tally(poll_id, 1), 1);
+        }
+
+        #[ink::test]
+        fn test_prune_expired_poll_snapshots_results() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = contract.create_poll(
+                String::from("Test Poll"),
+                String::from("Ends after 10 seconds"),
+                TEST_ROOT,
+                2,
+                10,
+            ).unwrap();
+            contract.cast_vote(poll_id, vec![1], [1u8; 32], 0).unwrap();
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(
+                10_000 + RESULT_RETENTION_MS + 1,
+            );
+            contract.prune_poll(poll_id).unwrap();
+
+            assert_eq!(contract.vote_tallies.get((poll_id, 0)), None);
+            assert_eq!(contract.get_all_tallies(poll_id), Ok(vec![1, 0]));
+        }
+
+        #[ink::test]
+        fn test_get_contract_info() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            contract.create_poll(
+                String::from("Test Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+
+            let info = contract.get_contract_info();
+            assert_eq!(info.owner, contract.owner);
+            assert_eq!(info.paused, contract.paused);
+            assert_eq!(info.verifier_address, contract.verifier_address);
+            assert_eq!(info.next_poll_id, contract.next_poll_id);
+            assert_eq!(info.next_poll_id, 2);
+            assert_eq!(info.total_polls, 1);
+            assert_eq!(info.active_polls, 1);
+            assert_eq!(info.distinct_creators, 1);
+
+            ink::env::test::set_caller(accounts.charlie);
+            let second = create_test_poll(&mut contract);
+            contract.close_poll(second).unwrap();
+
+            let info = contract.get_contract_info();
+            assert_eq!(info.total_polls, 2);
+            assert_eq!(info.active_polls, contract.active_poll_count());
+            assert_eq!(info.active_polls, 1);
+            assert_eq!(info.distinct_creators, contract.get_distinct_creator_count());
+            assert_eq!(info.distinct_creators, 2);
+        }
+
+        #[ink::test]
+        fn test_vote_cast_has_creator_topic() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            ink::env::test::set_caller(accounts.charlie);
+            let poll_id = create_test_poll(&mut contract);
+
+            ink::env::test::set_caller(accounts.django);
+            contract.cast_vote(poll_id, vec![1], [1u8; 32], 0).unwrap();
+
+            let events = ink::env::test::recorded_events();
+            let vote_cast = events.last().unwrap();
+            let mut creator_topic = [0u8; 32];
+            creator_topic[..20].copy_from_slice(accounts.charlie.as_bytes());
+            assert_eq!(vote_cast.topics.len(), 3);
+            assert_eq!(vote_cast.topics[2], creator_topic);
+        }
+
+        #[ink::test]
+        fn test_option_history() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = create_test_poll(&mut contract);
+
+            // Not tracked by default
+            contract.cast_vote(poll_id, vec![1], [1u8; 32], 0).unwrap();
+            assert!(contract.get_option_history(poll_id, 0, 10).is_empty());
+
+            let tracked = contract.create_poll(
+                String::from("Tracked Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+            contract
+                .update_poll_settings(tracked, PollSettings { track_history: true, ..Default::default() })
+                .unwrap();
+
+            for block in 1..=3u8 {
+                ink::env::test::set_block_number::<ink::env::DefaultEnvironment>(block.into());
+                contract.cast_vote(tracked, vec![1], [block; 32], 0).unwrap();
+            }
+            contract.cast_vote(tracked, vec![1], [9u8; 32], 1).unwrap();
+
+            assert_eq!(contract.get_option_history(tracked, 0, 10), vec![(1, 1), (2, 2), (3, 3)]);
+            assert_eq!(contract.get_option_history(tracked, 0, 2), vec![(2, 2), (3, 3)]);
+            assert_eq!(contract.get_option_history(tracked, 1, 10), vec![(3, 1)]);
+        }
+
+        #[ink::test]
+        fn test_option_history_is_bounded() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = contract.create_poll(
+                String::from("Tracked Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+            contract
+                .update_poll_settings(poll_id, PollSettings { track_history: true, ..Default::default() })
+                .unwrap();
+
+            let votes = MAX_HISTORY_SAMPLES as u8 + 5;
+            for i in 1..=votes {
+                contract.cast_vote(poll_id, vec![1], [i; 32], 0).unwrap();
+            }
+
+            let history = contract.get_option_history(poll_id, 0, u32::MAX);
+            assert_eq!(history.len(), MAX_HISTORY_SAMPLES);
+            assert_eq!(history.first().unwrap().1, 6);
+            assert_eq!(history.last().unwrap().1, u64::from(votes));
+        }
+
+        #[ink::test]
+        fn test_cheap_vote_checks_run_before_proof_work() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = create_test_poll(&mut contract);
+            contract.cast_vote(poll_id, vec![1], [1u8; 32], 0).unwrap();
+
+            // A proof that would fail verification is never looked at
+            assert_eq!(
+                contract.cast_vote(poll_id, vec![0], [2u8; 32], 5),
+                Err(Error::InvalidVoteOption)
+            );
+            assert_eq!(
+                contract.cast_vote(poll_id, vec![0], [1u8; 32], 0),
+                Err(Error::NullifierAlreadyUsed)
+            );
+
+            // Nor is a compressed proof that would fail to inflate
+            assert_eq!(
+                contract.cast_vote_compressed(poll_id, vec![0xff], [2u8; 32], 5),
+                Err(Error::InvalidVoteOption)
+            );
+            assert_eq!(
+                contract.cast_vote_compressed(poll_id, vec![0xff], [1u8; 32], 0),
+                Err(Error::NullifierAlreadyUsed)
+            );
+        }
+
+        #[ink::test]
+        fn test_get_polls() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let first = contract.create_poll(
+                String::from("First Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+            let second = contract.create_poll(
+                String::from("Second Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                3,
+                0,
+            ).unwrap();
+
+            let polls = contract.get_polls(vec![second, 99, first]).unwrap();
+            assert_eq!(polls.len(), 3);
+            assert_eq!(polls[0].as_ref().unwrap().title, "Second Poll");
+            assert_eq!(polls[1], None);
+            assert_eq!(polls[2].as_ref().unwrap().title, "First Poll");
+
+            assert_eq!(contract.get_polls(vec![first; MAX_BATCH_SIZE]).unwrap().len(), MAX_BATCH_SIZE);
+            assert_eq!(contract.get_polls(vec![first; MAX_BATCH_SIZE + 1]), Err(Error::BatchTooLarge));
+        }
+
+        #[ink::test]
+        fn test_open_poll_accepts_votes_without_proof() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = contract.create_poll(
+                String::from("Sentiment Poll"),
+                String::from("Open to everyone"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+            contract
+                .update_poll_settings(poll_id, PollSettings { open: true, ..Default::default() })
+                .unwrap();
+
+            contract.cast_open_vote(poll_id, [1u8; 32], 1).unwrap();
+            contract.cast_open_vote(poll_id, [2u8; 32], 1).unwrap();
+            assert_eq!(contract.get_all_tallies(poll_id), Ok(vec![0, 2]));
+
+            assert_eq!(
+                contract.cast_open_vote(poll_id, [1u8; 32], 0),
+                Err(Error::NullifierAlreadyUsed)
+            );
+            assert_eq!(
+                contract.cast_open_vote(poll_id, [3u8; 32], 2),
+                Err(Error::InvalidVoteOption)
+            );
+        }
+
+        #[ink::test]
+        fn test_gated_poll_still_requires_proof() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = create_test_poll(&mut contract);
+
+            assert_eq!(
+                contract.cast_open_vote(poll_id, [1u8; 32], 0),
+                Err(Error::NotAnonymousProofRequired)
+            );
+            assert_eq!(
+                contract.cast_vote(poll_id, vec![0], [1u8; 32], 0),
+                Err(Error::ProofVerificationFailed)
+            );
+            assert_eq!(contract.get_all_tallies(poll_id), Ok(vec![0, 0]));
+        }
+
+        #[ink::test]
+        fn test_validate_poll_config_matches_create_poll() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let long_title = "t".repeat(101);
+            let long_description = "d".repeat(501);
+            let configs = [
+                (String::from("Valid"), String::from("Valid"), [1u8; 32], 3, 60),
+                (long_title, String::from("Test"), [1u8; 32], 3, 0),
+                (String::from("Test"), long_description, [1u8; 32], 3, 0),
+                (String::from("Test"), String::from("Test"), [1u8; 32], 0, 0),
+                (String::from("Test"), String::from("Test"), [1u8; 32], 101, 0),
+                (String::from("Test"), String::from("Test"), [0u8; 32], 3, 0),
+                (String::from("Test"), String::from("Test"), [1u8; 32], 3, u64::MAX),
+            ];
+
+            for (title, description, merkle_root, max_options, duration) in configs {
+                let validated = contract.validate_poll_config(
+                    title.clone(),
+                    description.clone(),
+                    merkle_root,
+                    max_options,
+                    duration,
+                );
+                let total_polls = contract.get_total_polls();
+                let created = contract.create_poll(title, description, merkle_root, max_options, duration);
+
+                assert_eq!(validated, created.map(|_| ()));
+                if validated.is_ok() {
+                    assert_eq!(contract.get_total_polls(), total_polls + 1);
+                }
+            }
+        }
+
+        #[ink::test]
+        fn test_poll_admin_delegation() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            ink::env::test::set_caller(accounts.charlie);
+            let poll_id = create_test_poll(&mut contract);
+            contract.add_poll_admin(poll_id, accounts.django).unwrap();
+            assert!(contract.is_poll_admin(poll_id, accounts.django));
+
+            // Delegated admin manages the poll
+            ink::env::test::set_caller(accounts.django);
+            contract
+                .update_poll_settings(poll_id, PollSettings { eligible_count: 10, ..Default::default() })
+                .unwrap();
+            contract.grant_results_access(poll_id, accounts.frank).unwrap();
+            assert_eq!(
+                contract.add_poll_admin(poll_id, accounts.frank),
+                Err(Error::OnlyCreator)
+            );
+
+            // An unrelated account cannot
+            ink::env::test::set_caller(accounts.eve);
+            assert_eq!(
+                contract.update_poll_settings(poll_id, PollSettings::default()),
+                Err(Error::OnlyCreator)
+            );
+            assert_eq!(contract.close_poll(poll_id), Err(Error::OnlyOwner));
+
+            ink::env::test::set_caller(accounts.django);
+            contract.close_poll(poll_id).unwrap();
+        }
+
+        #[ink::test]
+        fn test_removed_poll_admin_loses_access() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            ink::env::test::set_caller(accounts.charlie);
+            let poll_id = create_test_poll(&mut contract);
+            contract.add_poll_admin(poll_id, accounts.django).unwrap();
+            contract.remove_poll_admin(poll_id, accounts.django).unwrap();
+            assert!(!contract.is_poll_admin(poll_id, accounts.django));
+
+            ink::env::test::set_caller(accounts.django);
+            assert_eq!(contract.close_poll(poll_id), Err(Error::OnlyOwner));
+        }
+
+        #[ink::test]
+        fn test_vote_rejection_reasons_are_distinct() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let expiring = contract.create_poll(
+                String::from("Short Poll"),
+                String::from("Ends after 10 seconds"),
+                TEST_ROOT,
+                2,
+                10,
+            ).unwrap();
+            let closed = contract.create_poll(
+                String::from("Closed Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+            let open = contract.create_poll(
+                String::from("Open Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+            contract.close_poll(closed).unwrap();
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(20_000);
+
+            assert_eq!(contract.cast_vote(expiring, vec![1], [1u8; 32], 0), Err(Error::PollEnded));
+            assert_eq!(contract.cast_vote(closed, vec![1], [1u8; 32], 0), Err(Error::PollNotActive));
+
+            contract.set_paused(true).unwrap();
+            assert!(contract.is_paused());
+            assert_eq!(contract.cast_vote(open, vec![1], [1u8; 32], 0), Err(Error::ContractPaused));
+
+            contract.set_paused(false).unwrap();
+            contract.cast_vote(open, vec![1], [1u8; 32], 0).unwrap();
+
+            ink::env::test::set_caller(accounts.charlie);
+            assert_eq!(contract.set_paused(true), Err(Error::OnlyOwner));
+        }
+
+        #[ink::test]
+        fn test_poll_outcome_spread_requirement() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = contract.create_poll(
+                String::from("Test Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                3,
+                0,
+            ).unwrap();
+            contract
+                .update_poll_settings(poll_id, PollSettings { quorum: 3, min_options_voted: 2, ..Default::default() })
+                .unwrap();
+
+            for i in 1..=3u8 {
+                contract.cast_vote(poll_id, vec![1], [i; 32], 0).unwrap();
+            }
+            assert_eq!(contract.get_poll_outcome(poll_id), Some(PollOutcome::Pending));
+
+            contract.close_poll(poll_id).unwrap();
+            assert_eq!(contract.get_poll_outcome(poll_id), Some(PollOutcome::FailedSpread));
+            assert_eq!(contract.get_poll_outcome(99), None);
+        }
+
+        #[ink::test]
+        fn test_poll_outcome_quorum_and_valid() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let settings = PollSettings { quorum: 2, min_options_voted: 2, ..Default::default() };
+            let short = contract.create_poll(
+                String::from("Short Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+            contract.update_poll_settings(short, settings.clone()).unwrap();
+            contract.cast_vote(short, vec![1], [1u8; 32], 0).unwrap();
+            contract.close_poll(short).unwrap();
+            assert_eq!(contract.get_poll_outcome(short), Some(PollOutcome::FailedQuorum));
+
+            let valid = contract.create_poll(
+                String::from("Valid Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+            contract.update_poll_settings(valid, settings).unwrap();
+            contract.cast_vote(valid, vec![1], [1u8; 32], 0).unwrap();
+            contract.cast_vote(valid, vec![1], [2u8; 32], 1).unwrap();
+            contract.close_poll(valid).unwrap();
+            assert_eq!(contract.get_poll_outcome(valid), Some(PollOutcome::Valid));
+        }
+
+        #[ink::test]
+        fn test_transfer_poll() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            ink::env::test::set_caller(accounts.charlie);
+            let kept = contract.create_poll(
+                String::from("Kept Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+            let handed_off = contract.create_poll(
+                String::from("Community Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+            assert_eq!(contract.get_polls_by_creator(accounts.charlie), vec![kept, handed_off]);
+
+            ink::env::test::set_caller(accounts.eve);
+            assert_eq!(contract.transfer_poll(handed_off, accounts.eve), Err(Error::OnlyCreator));
+
+            ink::env::test::set_caller(accounts.charlie);
+            contract.add_poll_admin(handed_off, accounts.frank).unwrap();
+            contract.grant_results_access(handed_off, accounts.frank).unwrap();
+            contract.transfer_poll(handed_off, accounts.django).unwrap();
+            assert_eq!(contract.get_pending_transfer(handed_off), Some(accounts.django));
+
+            // Nothing moves until the recipient accepts
+            assert_eq!(contract.get_polls_by_creator(accounts.django), Vec::<u128>::new());
+            assert_eq!(contract.accept_poll_transfer(handed_off), Err(Error::NoPendingTransfer));
+            ink::env::test::set_caller(accounts.eve);
+            assert_eq!(contract.accept_poll_transfer(handed_off), Err(Error::NoPendingTransfer));
+            ink::env::test::set_caller(accounts.django);
+            contract.accept_poll_transfer(handed_off).unwrap();
+            assert_eq!(contract.get_pending_transfer(handed_off), None);
+            assert_eq!(contract.accept_poll_transfer(handed_off), Err(Error::NoPendingTransfer));
+            ink::env::test::set_caller(accounts.charlie);
+
+            assert_eq!(contract.get_polls_by_creator(accounts.charlie), vec![kept]);
+            assert_eq!(contract.get_polls_by_creator(accounts.django), vec![handed_off]);
+            assert_eq!(contract.get_polls(vec![handed_off]).unwrap()[0].as_ref().unwrap().creator, accounts.django);
+
+            // Admin rights moved with the poll
+            assert_eq!(
+                contract.update_poll_settings(handed_off, PollSettings::default()),
+                Err(Error::OnlyCreator)
+            );
+            // Grants made by the previous creator do not survive the transfer
+            assert!(!contract.is_poll_admin(handed_off, accounts.frank));
+            ink::env::test::set_caller(accounts.frank);
+            assert_eq!(
+                contract.update_poll_settings(handed_off, PollSettings::default()),
+                Err(Error::OnlyCreator)
+            );
+            ink::env::test::set_caller(accounts.django);
+            contract.update_poll_settings(handed_off, PollSettings { embargo_results: true, ..Default::default() }).unwrap();
+            ink::env::test::set_caller(accounts.frank);
+            assert_eq!(contract.get_all_tallies(handed_off), Err(Error::ResultsEmbargoed));
+
+            // The new creator can delegate afresh
+            ink::env::test::set_caller(accounts.django);
+            contract.add_poll_admin(handed_off, accounts.frank).unwrap();
+            ink::env::test::set_caller(accounts.frank);
+            contract.update_poll_settings(handed_off, PollSettings::default()).unwrap();
+        }
+
+        #[ink::test]
+        fn test_get_creator_total_votes() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            ink::env::test::set_caller(accounts.charlie);
+            let first = contract.create_poll(
+                String::from("First Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+            let second = contract.create_poll(
+                String::from("Second Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+
+            ink::env::test::set_caller(accounts.django);
+            let other = contract.create_poll(
+                String::from("Other Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+
+            contract.cast_vote(first, vec![1], [1u8; 32], 0).unwrap();
+            contract.cast_vote(first, vec![1], [2u8; 32], 1).unwrap();
+            contract.cast_vote(second, vec![1], [3u8; 32], 0).unwrap();
+            contract.cast_vote(other, vec![1], [4u8; 32], 0).unwrap();
+
+            assert_eq!(contract.get_creator_total_votes(accounts.charlie), 3);
+            assert_eq!(contract.get_creator_total_votes(accounts.django), 1);
+            assert_eq!(contract.get_creator_total_votes(accounts.eve), 0);
+        }
+
+        #[ink::test]
+        fn test_bps() {
+            assert_eq!(bps(1, 4), 2_500);
+            assert_eq!(bps(1, 3), 3_333);
+            assert_eq!(bps(5, 0), 0);
+            assert_eq!(bps(0, 0), 0);
+            // Would overflow a u64 intermediate
+            assert_eq!(bps(u64::MAX, u64::MAX), 10_000);
+            assert_eq!(bps(u64::MAX / 2, u64::MAX), 4_999);
+            // Shares that don't fit a u32 saturate
+            assert_eq!(bps(u64::MAX, 1), u32::MAX);
+        }
+
+        #[ink::test]
+        fn test_get_results_with_percentages() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = contract.create_poll(
+                String::from("Test Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                3,
+                0,
+            ).unwrap();
+            assert_eq!(contract.get_results_with_percentages(poll_id, RoundingMode::Floor), Some(vec![(0, 0); 3]));
+
+            for i in 1..=3u8 {
+                contract.cast_vote(poll_id, vec![1], [i; 32], 0).unwrap();
+            }
+            contract.cast_vote(poll_id, vec![1], [4u8; 32], 2).unwrap();
+
+            assert_eq!(
+                contract.get_results_with_percentages(poll_id, RoundingMode::Floor),
+                Some(vec![(3, 7_500), (0, 0), (1, 2_500)])
+            );
+            assert_eq!(contract.get_results_with_percentages(99, RoundingMode::Floor), None);
+        }
+
+        #[ink::test]
+        fn test_is_final() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = contract.create_poll(
+                String::from("Test Poll"),
+                String::from("Ends after 10 seconds"),
+                TEST_ROOT,
+                2,
+                10,
+            ).unwrap();
+            assert_eq!(contract.is_final(poll_id), Some(false));
+
+            // Expired but not yet frozen
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(20_000);
+            assert_eq!(contract.is_final(poll_id), Some(false));
+
+            contract.close_poll(poll_id).unwrap();
+            assert_eq!(contract.is_final(poll_id), Some(true));
+            assert_eq!(contract.is_final(99), None);
+        }
+
+        #[ink::test]
+        fn test_dedupe_proofs_rejects_resubmitted_proof() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = contract.create_poll(
+                String::from("Dedupe Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+            contract
+                .update_poll_settings(poll_id, PollSettings { dedupe_proofs: true, ..Default::default() })
+                .unwrap();
+
+            contract.cast_vote(poll_id, vec![1, 2, 3], [1u8; 32], 0).unwrap();
+            assert_eq!(
+                contract.cast_vote(poll_id, vec![1, 2, 3], [2u8; 32], 0),
+                Err(Error::ProofAlreadySubmitted)
+            );
+            // The same bytes sent compressed are still a duplicate
+            assert_eq!(
+                contract.cast_vote_compressed(poll_id, compress_proof(&[1, 2, 3]), [2u8; 32], 0),
+                Err(Error::ProofAlreadySubmitted)
+            );
+            contract.cast_vote(poll_id, vec![1, 2, 4], [2u8; 32], 0).unwrap();
+            assert_eq!(contract.get_vote_tally(poll_id, 0), 2);
+        }
+
+        #[ink::test]
+        fn test_resubmitted_proof_allowed_without_dedupe() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = contract.create_poll(
+                String::from("Plain Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+
+            contract.cast_vote(poll_id, vec![1, 2, 3], [1u8; 32], 0).unwrap();
+            contract.cast_vote(poll_id, vec![1, 2, 3], [2u8; 32], 0).unwrap();
+            assert_eq!(contract.get_vote_tally(poll_id, 0), 2);
+        }
+
+        #[ink::test]
+        fn test_require_exact_options() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            assert_eq!(contract.set_circuit_options(0, true), Err(Error::InvalidPollParameters));
+            contract.set_circuit_options(4, true).unwrap();
+            assert_eq!(contract.get_circuit_options(), (4, true));
+
+            let create = |contract: &mut AnonymousPoll, max_options: u32| {
+                contract.create_poll(
+                    String::from("Fixed Circuit Poll"),
+                    String::from("Test"),
+                    [1u8; 32],
+                    max_options,
+                    0,
+                )
+            };
+            assert!(create(&mut contract, 4).is_ok());
+            assert_eq!(create(&mut contract, 3), Err(Error::InvalidPollParameters));
+            assert_eq!(create(&mut contract, 5), Err(Error::InvalidPollParameters));
+
+            // Without the requirement any option count is accepted again
+            contract.set_circuit_options(4, false).unwrap();
+            assert!(create(&mut contract, 3).is_ok());
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(contract.set_circuit_options(4, true), Err(Error::OnlyOwner));
+        }
+
+        #[ink::test]
+        fn test_debug_expected_inputs() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = contract.create_poll(
+                String::from("Debug Poll"),
+                String::from("Test"),
+                [7u8; 32],
+                3,
+                0,
+            ).unwrap();
+
+            let inputs = contract.debug_expected_inputs(poll_id, [9u8; 32]).unwrap();
+            assert_eq!(inputs, contract.encode_public_inputs([7u8; 32], [9u8; 32], poll_id, 3));
+            assert_eq!(&inputs[..32], &[7u8; 32]);
+            assert_eq!(&inputs[32..64], &[9u8; 32]);
+            assert_eq!(contract.debug_expected_inputs(99, [9u8; 32]), None);
+        }
+
+        #[ink::test]
+        fn test_get_field_modulus() {
+            let accounts = ink::env::test::default_accounts();
+            let contract = AnonymousPoll::new(accounts.bob);
+
+            assert_eq!(
+                contract.get_field_modulus(),
+                "21888242871839275222246405745257275088548364400416034343698204186575808495617"
+            );
+        }
+
+        #[ink::test]
+        fn test_end_time_overflow_is_rejected() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            // The duration in milliseconds does not fit in a u64
+            assert_eq!(contract.end_time(u64::MAX / 1000 + 1), Err(Error::InvalidPollParameters));
+
+            // Close to the end of time, now plus even one second overflows
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(u64::MAX - 500);
+            assert_eq!(contract.end_time(1), Err(Error::InvalidPollParameters));
+            assert_eq!(
+                contract.create_poll(String::from("Late Poll"), String::from("Test"), [1u8; 32], 2, 1),
+                Err(Error::InvalidPollParameters)
+            );
+            // Polls without an end time are unaffected
+            assert_eq!(contract.end_time(0), Ok(0));
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(5_000);
+            assert_eq!(contract.end_time(1), Ok(6_000));
+        }
+
+        #[ink::test]
+        fn test_get_voted_options_bitmap() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = contract.create_poll(
+                String::from("Wide Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                20,
+                0,
+            ).unwrap();
+
+            for (n, option) in [0u32, 9, 9, 19].into_iter().enumerate() {
+                contract.cast_vote(poll_id, vec![1], [n as u8 + 1; 32], option).unwrap();
+            }
+
+            let bitmap = contract.get_voted_options_bitmap(poll_id).unwrap();
+            assert_eq!(bitmap, vec![0b0000_0001, 0b0000_0010, 0b0000_1000]);
+
+            let tallies = contract.get_all_tallies(poll_id).unwrap();
+            for (option, count) in tallies.iter().enumerate() {
+                let bit = bitmap[option / 8] & (1 << (option % 8)) != 0;
+                assert_eq!(bit, *count > 0);
+            }
+            assert_eq!(contract.get_voted_options_bitmap(99), None);
+        }
+
+        #[ink::test]
+        fn test_set_poll_paused() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let paused_poll = contract.create_poll(
+                String::from("Paused Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+            let other_poll = contract.create_poll(
+                String::from("Other Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+
+            contract.set_poll_paused(paused_poll, true).unwrap();
+            assert!(contract.is_poll_paused(paused_poll));
+            assert_eq!(
+                contract.cast_vote(paused_poll, vec![1], [1u8; 32], 0),
+                Err(Error::PollPaused)
+            );
+            contract.cast_vote(other_poll, vec![1], [1u8; 32], 0).unwrap();
+
+            contract.set_poll_paused(paused_poll, false).unwrap();
+            assert!(!contract.is_poll_paused(paused_poll));
+            contract.cast_vote(paused_poll, vec![1], [1u8; 32], 0).unwrap();
+
+            ink::env::test::set_caller(accounts.charlie);
+            assert_eq!(contract.set_poll_paused(other_poll, true), Err(Error::OnlyCreator));
+        }
+
+        #[ink::test]
+        fn test_empty_proof_is_rejected() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = create_test_poll(&mut contract);
+
+            assert_eq!(contract.cast_vote(poll_id, vec![], [1u8; 32], 0), Err(Error::EmptyProof));
+            assert_eq!(
+                contract.cast_vote_compressed(poll_id, compress_proof(&[]), [1u8; 32], 0),
+                Err(Error::EmptyProof)
+            );
+            assert!(!contract.is_nullifier_used(poll_id, [1u8; 32]));
+        }
+
+        #[ink::test]
+        fn test_create_poll_with_allowlist() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = contract.create_poll_with_allowlist(
+                String::from("Allowlisted Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+                H160::from([3u8; 20]),
+            ).unwrap();
+            assert_eq!(contract.get_poll_settings(poll_id).unwrap().eligible_count, 30);
+
+            assert_eq!(
+                contract.create_poll_with_allowlist(
+                    String::from("Broken Allowlist"),
+                    String::from("Test"),
+                    [1u8; 32],
+                    2,
+                    0,
+                    H160::zero(),
+                ),
+                Err(Error::AllowlistCallFailed)
+            );
+            assert_eq!(contract.get_total_polls(), 1);
+        }
+
+        #[ink::test]
+        fn test_nullifier_equal_to_merkle_root_is_rejected() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = contract.create_poll(
+                String::from("Test Poll"),
+                String::from("Test"),
+                [5u8; 32],
+                2,
+                0,
+            ).unwrap();
+
+            assert_eq!(
+                contract.cast_vote(poll_id, vec![1], [5u8; 32], 0),
+                Err(Error::InvalidNullifierFormat)
+            );
+            contract.cast_vote(poll_id, vec![1], [6u8; 32], 0).unwrap();
+        }
+
+        #[ink::test]
+        fn test_chain_id_in_public_inputs() {
+            let accounts = ink::env::test::default_accounts();
+            let mainnet = AnonymousPoll::new_with_chain_id(accounts.bob, 1);
+            let fork = AnonymousPoll::new_with_chain_id(accounts.bob, 2);
+
+            assert_eq!(mainnet.get_chain_id(), 1);
+            assert_eq!(AnonymousPoll::new(accounts.bob).get_chain_id(), 0);
+
+            let inputs = mainnet.encode_public_inputs([1u8; 32], [2u8; 32], 1, 3);
+            assert_eq!(&inputs[inputs.len() - 4..], &1u32.to_be_bytes());
+            assert_ne!(inputs, fork.encode_public_inputs([1u8; 32], [2u8; 32], 1, 3));
+        }
+
+        #[ink::test]
+        fn test_publish_checkpoint() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = contract.create_poll(
+                String::from("Long Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+
+            contract.cast_vote(poll_id, vec![1], [1u8; 32], 0).unwrap();
+            assert_eq!(contract.publish_checkpoint(poll_id), Ok(0));
+
+            contract.cast_vote(poll_id, vec![1], [2u8; 32], 1).unwrap();
+            contract.cast_vote(poll_id, vec![1], [3u8; 32], 1).unwrap();
+            ink::env::test::set_block_number::<ink::env::DefaultEnvironment>(7);
+            assert_eq!(contract.publish_checkpoint(poll_id), Ok(1));
+
+            assert_eq!(contract.get_checkpoint(poll_id, 0), Some(vec![1, 0]));
+            assert_eq!(contract.get_checkpoint(poll_id, 1), Some(vec![1, 2]));
+            assert_eq!(contract.get_checkpoint(poll_id, 2), None);
+
+            let events = ink::env::test::recorded_events();
+            let checkpoint = events.last().unwrap();
+            let mut poll_topic = [0u8; 32];
+            poll_topic[..16].copy_from_slice(&poll_id.to_le_bytes());
+            assert_eq!(checkpoint.topics.len(), 2);
+            assert_eq!(checkpoint.topics[1], poll_topic);
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(contract.publish_checkpoint(poll_id), Err(Error::OnlyCreator));
+        }
+
+        #[ink::test]
+        fn test_max_votes_per_block() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = contract.create_poll(
+                String::from("Busy Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+            contract
+                .update_poll_settings(poll_id, PollSettings { max_votes_per_block: 2, ..Default::default() })
+                .unwrap();
+
+            ink::env::test::set_block_number::<ink::env::DefaultEnvironment>(1);
+            contract.cast_vote(poll_id, vec![1], [1u8; 32], 0).unwrap();
+            contract.cast_vote(poll_id, vec![1], [2u8; 32], 0).unwrap();
+            assert_eq!(contract.cast_vote(poll_id, vec![1], [3u8; 32], 0), Err(Error::RateLimited));
+
+            // The next block starts a fresh allowance
+            ink::env::test::set_block_number::<ink::env::DefaultEnvironment>(2);
+            contract.cast_vote(poll_id, vec![1], [3u8; 32], 0).unwrap();
+            assert_eq!(contract.get_vote_tally(poll_id, 0), 3);
+        }
+
+        #[ink::test]
+        fn test_get_poll_encoded_round_trip() {
+            use ink::scale::Decode;
+
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = contract.create_poll(
+                String::from("Cached Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                3,
+                60,
+            ).unwrap();
+
+            let bytes = contract.get_poll_encoded(poll_id).unwrap();
+            let decoded = Poll::decode(&mut &bytes[..]).unwrap();
+            assert_eq!(Some(decoded), contract.get_polls(vec![poll_id]).unwrap().remove(0));
+            assert_eq!(contract.get_poll_encoded(99), None);
+        }
+
+        #[ink::test]
+        fn test_record_vote_consumes_nullifier_first() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = contract.create_poll(
+                String::from("Prize Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+
+            // A re-entrant call would reach record_vote with a poll validated before
+            // the first vote landed
+            let stale = contract.validate_vote(poll_id, [1u8; 32], 0).unwrap();
+            contract.record_vote(stale.clone(), [1u8; 32], 0).unwrap();
+            assert_eq!(contract.record_vote(stale, [1u8; 32], 0), Err(Error::NullifierAlreadyUsed));
+            assert_eq!(contract.get_vote_tally(poll_id, 0), 1);
+        }
+
+        #[ink::test]
+        fn test_active_poll_count() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+            assert_eq!(contract.active_poll_count(), 0);
+
+            let closed = contract.create_poll(
+                String::from("Closed Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+            let expiring = contract.create_poll(
+                String::from("Expiring Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                10,
+            ).unwrap();
+            assert_eq!(contract.active_poll_count(), 2);
+
+            contract.close_poll(closed).unwrap();
+            assert_eq!(contract.active_poll_count(), 1);
+            // Closing twice does not count twice
+            contract.close_poll(closed).unwrap();
+            assert_eq!(contract.active_poll_count(), 1);
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(20_000);
+            assert_eq!(contract.active_poll_count(), 1);
+            contract.finalize_expired(vec![expiring]).unwrap();
+            assert_eq!(contract.active_poll_count(), 0);
+        }
+
+        #[ink::test]
+        fn test_largest_remainder_bps() {
+            assert_eq!(largest_remainder_bps(&[0, 0, 0]), vec![0, 0, 0]);
+            assert_eq!(largest_remainder_bps(&[1, 1, 1]), vec![3_334, 3_333, 3_333]);
+            assert_eq!(largest_remainder_bps(&[2, 1, 0]), vec![6_667, 3_333, 0]);
+            assert_eq!(largest_remainder_bps(&[u64::MAX, u64::MAX, 1]), vec![5_000, 5_000, 0]);
+
+            for counts in [vec![1, 2, 4], vec![5, 5, 5, 5, 1, 1, 1], vec![7], vec![1, 1, 1, 1, 1, 1]] {
+                assert_eq!(largest_remainder_bps(&counts).iter().sum::<u32>(), 10_000);
+            }
+        }
+
+        #[ink::test]
+        fn test_results_rounding_modes() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = contract.create_poll(
+                String::from("Three Way Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                3,
+                0,
+            ).unwrap();
+            for option in 0..3u32 {
+                contract.cast_vote(poll_id, vec![1], [option as u8 + 1; 32], option).unwrap();
+            }
+
+            let floor = contract.get_results_with_percentages(poll_id, RoundingMode::Floor).unwrap();
+            assert_eq!(floor, vec![(1, 3_333); 3]);
+            assert_eq!(floor.iter().map(|(_, share)| share).sum::<u32>(), 9_999);
+
+            let apportioned = contract
+                .get_results_with_percentages(poll_id, RoundingMode::LargestRemainder)
+                .unwrap();
+            assert_eq!(apportioned, vec![(1, 3_334), (1, 3_333), (1, 3_333)]);
+            assert_eq!(apportioned.iter().map(|(_, share)| share).sum::<u32>(), 10_000);
+        }
+
+        #[ink::test]
+        fn test_quorum_reached_event_fires_once() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = contract.create_poll(
+                String::from("Quorum Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+            contract
+                .update_poll_settings(poll_id, PollSettings { quorum: 2, ..Default::default() })
+                .unwrap();
+
+            let mut events_per_vote = Vec::new();
+            for i in 1..=4u8 {
+                let before = ink::env::test::recorded_events().len();
+                contract.cast_vote(poll_id, vec![1], [i; 32], 0).unwrap();
+                events_per_vote.push(ink::env::test::recorded_events().len() - before);
+            }
+            // VoteCast on every vote, plus QuorumReached on the second only
+            assert_eq!(events_per_vote, vec![1, 2, 1, 1]);
+        }
+
+        #[ink::test]
+        fn test_partial_prize_claim() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            ink::env::test::set_value_transferred(U256::from(1_000));
+            let poll_id = contract.create_poll(
+                String::from("Prize Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+            ink::env::test::set_value_transferred(U256::zero());
+            ink::env::test::set_contract_balance(ink::env::test::callee(), U256::from(1_000));
+            assert_eq!(contract.get_unclaimed_prize(poll_id), Some(U256::from(1_000)));
+
+            assert_eq!(
+                contract.claim_prize(poll_id, accounts.django, U256::from(400)),
+                Err(Error::PollNotEnded)
+            );
+            contract.close_poll(poll_id).unwrap();
+
+            contract.claim_prize(poll_id, accounts.django, U256::from(400)).unwrap();
+            assert_eq!(contract.get_unclaimed_prize(poll_id), Some(U256::from(600)));
+            assert_eq!(
+                contract.claim_prize(poll_id, accounts.django, U256::from(601)),
+                Err(Error::InsufficientPrize)
+            );
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(
+                contract.claim_prize(poll_id, accounts.bob, U256::from(1)),
+                Err(Error::OnlyCreator)
+            );
+            assert_eq!(contract.get_unclaimed_prize(99), None);
+        }
+
+        #[ink::test]
+        fn test_nullifier_accumulator_root() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let create = |contract: &mut AnonymousPoll| {
+                contract.create_poll(
+                    String::from("Audited Poll"),
+                    String::from("Test"),
+                    TEST_ROOT,
+                    2,
+                    0,
+                ).unwrap()
+            };
+            let first = create(&mut contract);
+            let second = create(&mut contract);
+
+            // Reference root built level by level from the leaves, padding with empty subtrees
+            let reference_root = |contract: &AnonymousPoll, leaves: &[[u8; 32]]| {
+                let mut level = leaves.to_vec();
+                let mut zero = [0u8; 32];
+                for _ in 0..NULLIFIER_TREE_DEPTH {
+                    if level.len() % 2 == 1 {
+                        level.push(zero);
+                    }
+                    level = level.chunks(2).map(|pair| contract.hash_pair(&pair[0], &pair[1])).collect();
+                    zero = contract.hash_pair(&zero, &zero);
+                }
+                level[0]
+            };
+
+            let empty = contract.get_nullifier_accumulator_root(first).unwrap();
+            assert_eq!(empty, reference_root(&contract, &[[0u8; 32]]));
+
+            let nullifiers = [[1u8; 32], [2u8; 32], [3u8; 32]];
+            let mut roots = vec![empty];
+            for (count, nullifier) in nullifiers.iter().enumerate() {
+                contract.cast_vote(first, vec![1], *nullifier, 0).unwrap();
+                let root = contract.get_nullifier_accumulator_root(first).unwrap();
+                assert_eq!(root, reference_root(&contract, &nullifiers[..=count]));
+                assert!(!roots.contains(&root));
+                roots.push(root);
+            }
+
+            // Same nullifiers in another order give a different root
+            for nullifier in [[2u8; 32], [1u8; 32], [3u8; 32]] {
+                contract.cast_vote(second, vec![1], nullifier, 0).unwrap();
+            }
+            assert_ne!(contract.get_nullifier_accumulator_root(second), Some(roots[3]));
+            assert_eq!(contract.get_nullifier_accumulator_root(99), None);
+        }
+
+        #[ink::test]
+        fn test_set_circuit_config() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let config = CircuitConfig {
+                max_options: 4,
+                require_exact_options: true,
+                max_proof_bytes: 8,
+                min_proof_bytes: 2,
+                allowed_proof_systems: vec![ProofSystem::UltraHonk, ProofSystem::UltraPlonk],
+            };
+            contract.set_circuit_config(config.clone()).unwrap();
+            assert_eq!(contract.get_circuit_config(), config);
+            assert_eq!(contract.get_circuit_options(), (4, true));
+            assert_eq!(contract.get_allowed_proof_systems(), config.allowed_proof_systems);
+
+            let poll_id = contract.create_poll(
+                String::from("Configured Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                4,
+                0,
+            ).unwrap();
+            assert_eq!(contract.cast_vote(poll_id, vec![1; 9], [1u8; 32], 0), Err(Error::ProofTooLarge));
+            contract.cast_vote(poll_id, vec![1; 8], [1u8; 32], 0).unwrap();
+
+            // An invalid config leaves every field untouched
+            let invalid = CircuitConfig { max_proof_bytes: 0, require_exact_options: false, ..config.clone() };
+            assert_eq!(contract.set_circuit_config(invalid), Err(Error::InvalidPollParameters));
+            assert_eq!(contract.get_circuit_config(), config);
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(contract.set_circuit_config(config), Err(Error::OnlyOwner));
+        }
+
+        #[ink::test]
+        fn test_get_ended_at_records_close_block() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = contract.create_poll(
+                String::from("Early Close"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                3600,
+            ).unwrap();
+            assert_eq!(contract.get_ended_at(poll_id), None);
+
+            ink::env::test::set_block_number::<ink::env::DefaultEnvironment>(12);
+            contract.close_poll(poll_id).unwrap();
+            assert_eq!(contract.get_ended_at(poll_id), Some(12));
+
+            // Closing again keeps the original block
+            ink::env::test::set_block_number::<ink::env::DefaultEnvironment>(20);
+            contract.close_poll(poll_id).unwrap();
+            assert_eq!(contract.get_ended_at(poll_id), Some(12));
+        }
+
+        #[ink::test]
+        fn test_get_voter_status() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = contract.create_poll(
+                String::from("Status Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                3,
+                0,
+            ).unwrap();
+            contract
+                .update_poll_settings(poll_id, PollSettings { store_choices: true, ..Default::default() })
+                .unwrap();
+
+            let before = contract.get_voter_status(poll_id, [1u8; 32]).unwrap();
+            assert_eq!(before, VoterStatus {
+                can_vote: true,
+                nullifier_used: false,
+                has_recorded_choice: false,
+                recorded_choice: 0,
+            });
+
+            contract.cast_vote(poll_id, vec![1], [1u8; 32], 2).unwrap();
+            let after = contract.get_voter_status(poll_id, [1u8; 32]).unwrap();
+            assert_eq!(after, VoterStatus {
+                can_vote: false,
+                nullifier_used: true,
+                has_recorded_choice: true,
+                recorded_choice: 2,
+            });
+            assert_eq!(contract.get_voter_status(99, [1u8; 32]), None);
+        }
+
+        #[ink::test]
+        fn test_undersized_proof_is_rejected() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let mut config = contract.get_circuit_config();
+            config.min_proof_bytes = 64;
+            contract.set_circuit_config(config.clone()).unwrap();
+
+            let poll_id = create_test_poll(&mut contract);
+
+            assert_eq!(contract.cast_vote(poll_id, vec![1; 9], [1u8; 32], 0), Err(Error::ProofTooSmall));
+            contract.cast_vote(poll_id, vec![1; 64], [1u8; 32], 0).unwrap();
+
+            config.min_proof_bytes = config.max_proof_bytes + 1;
+            assert_eq!(contract.set_circuit_config(config), Err(Error::InvalidPollParameters));
+        }
+
+        #[ink::test]
+        fn test_get_polls_ending_soon() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let mut create = |duration_seconds: u64| {
+                contract.create_poll(
+                    String::from("Reminder Poll"),
+                    String::from("Test"),
+                    TEST_ROOT,
+                    2,
+                    duration_seconds,
+                ).unwrap()
+            };
+            let in_ten = create(10);
+            let in_sixty = create(60);
+            let no_end = create(0);
+            let in_hour = create(3600);
+            let closed = create(30);
+            contract.close_poll(closed).unwrap();
+
+            assert_eq!(contract.get_polls_ending_soon(60_000, 1, 10), vec![in_ten, in_sixty]);
+            assert_eq!(contract.get_polls_ending_soon(10_000, 1, 10), vec![in_ten]);
+            // Pagination only scans the requested id range
+            assert_eq!(contract.get_polls_ending_soon(3_600_000, 2, 2), vec![in_sixty]);
+            assert_eq!(contract.get_polls_ending_soon(3_600_000, 3, 10), vec![in_hour]);
+            assert!(!contract.get_polls_ending_soon(u64::MAX, 1, 10).contains(&no_end));
+
+            // Polls that already ended are no longer "ending soon"
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(20_000);
+            assert_eq!(contract.get_polls_ending_soon(60_000, 1, 10), vec![in_sixty]);
+        }
+
+        #[ink::test]
+        fn test_store_choices() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let mut create = || {
+                contract.create_poll(
+                    String::from("Choice Poll"),
+                    String::from("Test"),
+                    TEST_ROOT,
+                    3,
+                    0,
+                ).unwrap()
+            };
+            let storing = create();
+            let private = create();
+            contract
+                .update_poll_settings(storing, PollSettings { store_choices: true, ..Default::default() })
+                .unwrap();
+
+            assert_eq!(contract.get_recorded_choice(storing, [1u8; 32]), Ok(None));
+            contract.cast_vote(storing, vec![1], [1u8; 32], 2).unwrap();
+            assert_eq!(contract.get_recorded_choice(storing, [1u8; 32]), Ok(Some(2)));
+
+            contract.cast_vote(private, vec![1], [1u8; 32], 2).unwrap();
+            assert_eq!(contract.get_recorded_choice(private, [1u8; 32]), Err(Error::ChoiceNotStored));
+            let status = contract.get_voter_status(private, [1u8; 32]).unwrap();
+            assert!(status.nullifier_used);
+            assert!(!status.has_recorded_choice);
+        }
+
+        #[ink::test]
+        fn test_verifier_updated_events() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let mut deployer_topic = [0u8; 32];
+            deployer_topic[..20].copy_from_slice(accounts.alice.as_bytes());
+
+            let events = ink::env::test::recorded_events();
+            assert_eq!(events.len(), 1);
+            assert_eq!(events[0].topics[1], deployer_topic);
+
+            contract.update_verifier(accounts.charlie).unwrap();
+            let events = ink::env::test::recorded_events();
+            assert_eq!(events.len(), 2);
+            assert_eq!(events[1].topics[1], deployer_topic);
+        }
+
+        #[ink::test]
+        fn test_get_margin() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let mut create = |max_options: u32| {
+                contract.create_poll(
+                    String::from("Close Race"),
+                    String::from("Test"),
+                    TEST_ROOT,
+                    max_options,
+                    0,
+                ).unwrap()
+            };
+            let clear = create(3);
+            let tied = create(2);
+            let single = create(1);
+
+            for (i, option) in [1u32, 1, 1, 0, 2].into_iter().enumerate() {
+                contract.cast_vote(clear, vec![1], [i as u8 + 1; 32], option).unwrap();
+            }
+            assert_eq!(contract.get_margin(clear), Some(2));
+
+            contract.cast_vote(tied, vec![1], [1u8; 32], 0).unwrap();
+            contract.cast_vote(tied, vec![1], [2u8; 32], 1).unwrap();
+            assert_eq!(contract.get_margin(tied), Some(0));
+
+            contract.cast_vote(single, vec![1], [1u8; 32], 0).unwrap();
+            contract.cast_vote(single, vec![1], [2u8; 32], 0).unwrap();
+            assert_eq!(contract.get_margin(single), Some(2));
+            assert_eq!(contract.get_margin(99), None);
+        }
+
+        #[ink::test]
+        fn test_get_poll_proof_info() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+            contract
+                .set_allowed_proof_systems(vec![ProofSystem::UltraHonk, ProofSystem::UltraPlonk])
+                .unwrap();
+
+            let poll_id = contract.create_poll(
+                String::from("Plonk Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+            assert_eq!(contract.get_poll_proof_info(poll_id), Some((ProofSystem::UltraHonk, accounts.bob)));
+
+            contract
+                .update_poll_settings(poll_id, PollSettings { proof_system: ProofSystem::UltraPlonk, ..Default::default() })
+                .unwrap();
+            assert_eq!(contract.get_poll_proof_info(poll_id), Some((ProofSystem::UltraPlonk, accounts.bob)));
+            assert_eq!(contract.get_poll_proof_info(99), None);
+        }
+
+        #[ink::test]
+        fn test_auto_close_threshold() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = contract.create_poll(
+                String::from("Race Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+            contract
+                .update_poll_settings(poll_id, PollSettings { auto_close_threshold: 2, ..Default::default() })
+                .unwrap();
+
+            contract.cast_vote(poll_id, vec![1], [1u8; 32], 0).unwrap();
+            contract.cast_vote(poll_id, vec![1], [2u8; 32], 1).unwrap();
+            assert_eq!(contract.is_final(poll_id), Some(false));
+
+            contract.cast_vote(poll_id, vec![1], [3u8; 32], 1).unwrap();
+            assert_eq!(contract.is_final(poll_id), Some(true));
+            assert_eq!(contract.get_all_tallies(poll_id), Ok(vec![1, 2]));
+            assert_eq!(contract.active_poll_count(), 0);
+            assert_eq!(
+                contract.cast_vote(poll_id, vec![1], [4u8; 32], 0),
+                Err(Error::PollNotActive)
+            );
+        }
+
+        #[ink::test]
+        fn test_withdraw_keeps_prize_pools() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            ink::env::test::set_value_transferred(U256::from(600));
+            contract.create_poll(
+                String::from("Prize Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+            ink::env::test::set_value_transferred(U256::zero());
+
+            // 600 earmarked for the prize, 400 sent by mistake
+            ink::env::test::set_contract_balance(ink::env::test::callee(), U256::from(1_000));
+
+            assert_eq!(
+                contract.withdraw(accounts.django, U256::from(401)),
+                Err(Error::InsufficientBalance)
+            );
+            contract.withdraw(accounts.django, U256::from(400)).unwrap();
+            assert_eq!(
+                contract.withdraw(accounts.django, U256::from(1)),
+                Err(Error::InsufficientBalance)
+            );
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(contract.withdraw(accounts.bob, U256::zero()), Err(Error::OnlyOwner));
+        }
+
+        #[ink::test]
+        fn test_open_poll_proof_of_work() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = contract.create_poll(
+                String::from("Spam Guarded Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+            contract
+                .update_poll_settings(poll_id, PollSettings { open: true, ..Default::default() })
+                .unwrap();
+            contract.set_pow_difficulty(poll_id, 1).unwrap();
+            assert_eq!(contract.get_pow_difficulty(poll_id), 1);
+
+            // Mine one nullifier meeting the target and find one below it
+            let mut mined = None;
+            let mut weak = None;
+            for nonce in 0..=u16::MAX {
+                let mut nullifier = [7u8; 32];
+                nullifier[30..].copy_from_slice(&nonce.to_be_bytes());
+                if contract.meets_pow(poll_id, nullifier, 1) {
+                    mined.get_or_insert(nullifier);
+                } else {
+                    weak.get_or_insert(nullifier);
+                }
+                if mined.is_some() && weak.is_some() {
+                    break;
+                }
+            }
+
+            assert_eq!(
+                contract.cast_open_vote(poll_id, weak.unwrap(), 0),
+                Err(Error::InsufficientProofOfWork)
+            );
+            contract.cast_open_vote(poll_id, mined.unwrap(), 0).unwrap();
+            assert_eq!(contract.set_pow_difficulty(poll_id, 2), Err(Error::PollHasVotes));
+        }
+
+        #[ink::test]
+        fn test_get_total_polls_counts_every_creation() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let mut ids = Vec::new();
+            for expected in 1..=3u128 {
+                ids.push(contract.create_poll(
+                    String::from("Counted Poll"),
+                    String::from("Test"),
+                    TEST_ROOT,
+                    2,
+                    0,
+                ).unwrap());
+                assert_eq!(contract.get_total_polls(), expected);
+            }
+
+            // Closing and pruning a poll leaves the total unchanged
+            contract.close_poll(ids[0]).unwrap();
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(RESULT_RETENTION_MS + 1);
+            contract.prune_poll(ids[0]).unwrap();
+            assert_eq!(contract.get_total_polls(), 3);
+            assert_eq!(contract.active_poll_count(), 2);
+        }
+
+        #[ink::test]
+        fn test_merkle_root_must_be_field_element() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            assert_eq!(
+                U256::from_big_endian(&BN254_MODULUS_BYTES),
+                U256::from_dec_str(BN254_MODULUS).unwrap()
+            );
+
+            let mut create = |merkle_root: [u8; 32]| {
+                contract.create_poll(String::from("Root Poll"), String::from("Test"), merkle_root, 2, 0)
+            };
+            let mut largest = BN254_MODULUS_BYTES;
+            largest[31] -= 1;
+            assert!(create(largest).is_ok());
+            assert_eq!(create(BN254_MODULUS_BYTES), Err(Error::InvalidMerkleRoot));
+            assert_eq!(create([0xffu8; 32]), Err(Error::InvalidMerkleRoot));
+        }
+
+        #[ink::test]
+        fn test_get_results_batch() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let mut create = || {
+                contract.create_poll(
+                    String::from("Dashboard Poll"),
+                    String::from("Test"),
+                    TEST_ROOT,
+                    2,
+                    0,
+                ).unwrap()
+            };
+            let first = create();
+            let second = create();
+            contract.cast_vote(second, vec![1], [1u8; 32], 1).unwrap();
+
+            assert_eq!(
+                contract.get_results_batch(vec![second, 99, first]),
+                Ok(vec![Some(vec![0, 1]), None, Some(vec![0, 0])])
+            );
+            assert_eq!(contract.get_results_batch(vec![first; MAX_BATCH_SIZE]).unwrap().len(), MAX_BATCH_SIZE);
+            assert_eq!(
+                contract.get_results_batch(vec![first; MAX_BATCH_SIZE + 1]),
+                Err(Error::BatchTooLarge)
+            );
+        }
+
+        #[ink::test]
+        fn test_new_with_owner() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new_with_owner(accounts.bob, accounts.charlie);
+            assert_eq!(contract.get_owner(), accounts.charlie);
+
+            // The deploy-time VerifierUpdated is attributed to the owner, not the deployer
+            let events = ink::env::test::recorded_events();
+            assert_eq!(events.len(), 1);
+            let mut owner_topic = [0u8; 32];
+            owner_topic[..20].copy_from_slice(accounts.charlie.as_bytes());
+            assert_eq!(events[0].topics[1], owner_topic);
+
+            // The deployer has no admin rights
+            assert_eq!(contract.set_paused(true), Err(Error::OnlyOwner));
+            assert_eq!(contract.update_verifier(accounts.django), Err(Error::OnlyOwner));
+
+            ink::env::test::set_caller(accounts.charlie);
+            contract.set_paused(true).unwrap();
+            contract.update_verifier(accounts.django).unwrap();
+            assert_eq!(contract.get_verifier_address(), accounts.django);
+        }
+
+        #[ink::test]
+        fn test_revoke_nullifier() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = contract.create_poll(
+                String::from("Revocable Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+
+            contract.revoke_nullifier(poll_id, [1u8; 32]).unwrap();
+            assert!(contract.is_nullifier_revoked(poll_id, [1u8; 32]));
+            assert_eq!(
+                contract.cast_vote(poll_id, vec![1], [1u8; 32], 0),
+                Err(Error::EligibilityRevoked)
+            );
+            contract.cast_vote(poll_id, vec![1], [2u8; 32], 0).unwrap();
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(contract.revoke_nullifier(poll_id, [3u8; 32]), Err(Error::OnlyCreator));
+        }
+
+        #[ink::test]
+        fn test_meets_quorum() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = contract.create_poll(
+                String::from("Quorum Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                3600,
+            ).unwrap();
+            assert_eq!(contract.meets_quorum(poll_id), Some(true));
+
+            contract
+                .update_poll_settings(poll_id, PollSettings { quorum: 2, ..Default::default() })
+                .unwrap();
+            contract.cast_vote(poll_id, vec![1], [1u8; 32], 0).unwrap();
+            assert_eq!(contract.meets_quorum(poll_id), Some(false));
+
+            contract.cast_vote(poll_id, vec![1], [2u8; 32], 1).unwrap();
+            // Reported while the poll is still running
+            assert_eq!(contract.get_poll_outcome(poll_id), Some(PollOutcome::Pending));
+            assert_eq!(contract.meets_quorum(poll_id), Some(true));
+            assert_eq!(contract.meets_quorum(99), None);
+        }
+
+        #[ink::test]
+        fn test_upgrade_only_owner() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(contract.upgrade([1u8; 32]), Err(Error::OnlyOwner));
+        }
+
+        #[ink::test]
+        fn test_get_option_count() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = contract.create_poll(
+                String::from("Five Options"),
+                String::from("Test"),
+                TEST_ROOT,
+                5,
+                0,
+            ).unwrap();
+
+            let (_, _, _, _, _, max_options, _, _, _, _, _) = contract.get_poll(poll_id);
+            assert_eq!(contract.get_option_count(poll_id), Some(max_options));
+            assert_eq!(contract.get_option_count(poll_id), Some(5));
+            assert_eq!(contract.get_option_count(99), None);
+        }
+
+        #[ink::test]
+        fn test_grace_period_late_votes() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = contract.create_poll(
+                String::from("Graceful Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                10,
+            ).unwrap();
+            contract.set_grace_period(poll_id, 5_000).unwrap();
+            contract.cast_vote(poll_id, vec![1], [1u8; 32], 0).unwrap();
+
+            // Within the grace window the vote is verified but kept apart
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(12_000);
+            contract.cast_vote(poll_id, vec![1], [2u8; 32], 1).unwrap();
+            assert_eq!(contract.get_all_tallies(poll_id), Ok(vec![1, 0]));
+            assert_eq!(contract.get_late_results(poll_id), vec![0, 1]);
+            assert!(contract.is_nullifier_used(poll_id, [2u8; 32]));
+            assert_eq!(
+                contract.cast_vote(poll_id, vec![0], [3u8; 32], 1),
+                Err(Error::ProofVerificationFailed)
+            );
+            assert_eq!(contract.include_late_votes(poll_id), Err(Error::PollNotEnded));
+
+            // Beyond the grace window votes are rejected
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(15_001);
+            assert_eq!(contract.cast_vote(poll_id, vec![1], [3u8; 32], 1), Err(Error::PollEnded));
+
+            assert_eq!(contract.include_late_votes(poll_id), Ok(1));
+            assert_eq!(contract.get_all_tallies(poll_id), Ok(vec![1, 1]));
+            assert_eq!(contract.get_late_results(poll_id), vec![0, 0]);
+            assert_eq!(contract.include_late_votes(poll_id), Ok(0));
+        }
+
+        #[ink::test]
+        fn test_get_poll_config_hash() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let mut create = || {
+                contract.create_poll(
+                    String::from("Signed Poll"),
+                    String::from("Test"),
+                    TEST_ROOT,
+                    2,
+                    60,
+                ).unwrap()
+            };
+            let first = create();
+            let second = create();
+
+            let hash = contract.get_poll_config_hash(first).unwrap();
+            assert_ne!(Some(hash), contract.get_poll_config_hash(second));
+
+            // Votes don't touch the configuration
+            contract.cast_vote(first, vec![1], [1u8; 32], 0).unwrap();
+            assert_eq!(contract.get_poll_config_hash(first), Some(hash));
+
+            contract.transfer_poll(first, accounts.charlie).unwrap();
+            ink::env::test::set_caller(accounts.charlie);
+            contract.accept_poll_transfer(first).unwrap();
+            assert_ne!(contract.get_poll_config_hash(first), Some(hash));
+            assert_eq!(contract.get_poll_config_hash(99), None);
+        }
+
+        #[ink::test]
+        fn test_get_viable_options() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = contract.create_poll(
+                String::from("Seats Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                4,
+                0,
+            ).unwrap();
+            assert_eq!(contract.get_viable_options(poll_id), Some(vec![0, 1, 2, 3]));
+
+            contract.set_viability_threshold(poll_id, 2).unwrap();
+            for (i, option) in [0u32, 0, 1, 2, 2, 2].into_iter().enumerate() {
+                contract.cast_vote(poll_id, vec![1], [i as u8 + 1; 32], option).unwrap();
+            }
+            assert_eq!(contract.get_viable_options(poll_id), Some(vec![0, 2]));
+            assert_eq!(contract.set_viability_threshold(poll_id, 1), Err(Error::PollHasVotes));
+            assert_eq!(contract.get_viable_options(99), None);
+        }
+
+        #[ink::test]
+        fn test_get_limits() {
+            let accounts = ink::env::test::default_accounts();
+            let contract = AnonymousPoll::new(accounts.bob);
+
+            let limits = contract.get_limits();
+            assert_eq!(limits.max_batch_size as usize, MAX_BATCH_SIZE);
+            assert_eq!(limits.max_proof_size as usize, MAX_PROOF_SIZE);
+            assert_eq!(limits.max_history_samples as usize, MAX_HISTORY_SAMPLES);
+            assert!(contract.get_polls(vec![0; limits.max_batch_size as usize]).is_ok());
+            assert_eq!(
+                contract.get_polls(vec![0; limits.max_batch_size as usize + 1]),
+                Err(Error::BatchTooLarge)
+            );
+        }
+
+        #[ink::test]
+        fn test_mismatched_vote_path_is_rejected() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let open_poll = contract.create_poll(
+                String::from("Open Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+            contract
+                .update_poll_settings(open_poll, PollSettings { open: true, ..Default::default() })
+                .unwrap();
+            let gated_poll = contract.create_poll(
+                String::from("Gated Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+
+            // A valid proof is still refused on an open poll
+            assert_eq!(
+                contract.cast_vote(open_poll, vec![1], [1u8; 32], 0),
+                Err(Error::WrongVotingMode)
+            );
+            assert_eq!(
+                contract.cast_vote_compressed(open_poll, compress_proof(&[1]), [1u8; 32], 0),
+                Err(Error::WrongVotingMode)
+            );
+            assert_eq!(
+                contract.cast_open_vote(gated_poll, [1u8; 32], 0),
+                Err(Error::NotAnonymousProofRequired)
+            );
+            assert!(!contract.is_nullifier_used(open_poll, [1u8; 32]));
+            assert!(!contract.is_nullifier_used(gated_poll, [1u8; 32]));
+
+            contract.cast_open_vote(open_poll, [1u8; 32], 0).unwrap();
+            contract.cast_vote(gated_poll, vec![1], [1u8; 32], 0).unwrap();
+        }
+
+        #[ink::test]
+        fn test_distinct_creator_count() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+            assert_eq!(contract.get_distinct_creator_count(), 0);
+
+            for (creator, expected) in [
+                (accounts.alice, 1),
+                (accounts.alice, 1),
+                (accounts.charlie, 2),
+                (accounts.alice, 2),
+                (accounts.django, 3),
+            ] {
+                ink::env::test::set_caller(creator);
+                contract.create_poll(
+                    String::from("Test Poll"),
+                    String::from("Test"),
+                    TEST_ROOT,
+                    2,
+                    0,
+                ).unwrap();
+                assert_eq!(contract.get_distinct_creator_count(), expected);
+            }
+            assert_eq!(contract.get_total_polls(), 5);
+        }
+
+        #[ink::test]
+        fn test_votes_in_block() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let tracked = contract.create_poll(
+                String::from("Tracked Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+            let untracked = contract.create_poll(
+                String::from("Untracked Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+            contract.set_block_vote_counts(tracked, true).unwrap();
+
+            ink::env::test::set_block_number::<ink::env::DefaultEnvironment>(10);
+            contract.cast_vote(tracked, vec![1], [1u8; 32], 0).unwrap();
+            contract.cast_vote(tracked, vec![1], [2u8; 32], 1).unwrap();
+            contract.cast_vote(untracked, vec![1], [1u8; 32], 0).unwrap();
+
+            ink::env::test::set_block_number::<ink::env::DefaultEnvironment>(11);
+            contract.cast_vote(tracked, vec![1], [3u8; 32], 0).unwrap();
+
+            assert_eq!(contract.get_votes_in_block(tracked, 10), 2);
+            assert_eq!(contract.get_votes_in_block(tracked, 11), 1);
+            assert_eq!(contract.get_votes_in_block(tracked, 12), 0);
+            assert_eq!(contract.get_votes_in_block(untracked, 10), 0);
+            assert_eq!(contract.set_block_vote_counts(tracked, false), Err(Error::PollHasVotes));
+        }
+
+        #[ink::test]
+        fn test_update_merkle_root() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = create_test_poll(&mut contract);
+
+            assert_eq!(contract.update_merkle_root(poll_id, [0u8; 32]), Err(Error::InvalidMerkleRoot));
+            assert_eq!(contract.update_merkle_root(poll_id, [0xffu8; 32]), Err(Error::InvalidMerkleRoot));
+            contract.update_merkle_root(poll_id, [0x2cu8; 32]).unwrap();
+            assert_eq!(contract.get_merkle_root(poll_id), Some([0x2cu8; 32]));
+
+            ink::env::test::set_caller(accounts.charlie);
+            assert_eq!(contract.update_merkle_root(poll_id, [0x2du8; 32]), Err(Error::OnlyCreator));
+
+            // Once a vote exists the root is frozen
+            contract.cast_vote(poll_id, vec![1], [1u8; 32], 0).unwrap();
+            ink::env::test::set_caller(accounts.alice);
+            assert_eq!(contract.update_merkle_root(poll_id, [0x2du8; 32]), Err(Error::PollHasVotes));
+            assert_eq!(contract.get_merkle_root(poll_id), Some([0x2cu8; 32]));
+        }
+
+        #[ink::test]
+        fn test_is_valid_option() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = contract.create_poll(
                 String::from("Test Poll"),
                 String::from("Test"),
-                [1u8; 32],
+                TEST_ROOT,
                 3,
                 0,
             ).unwrap();
 
-            // All tallies should start at 0
-            let tallies = contract.get_all_tallies(poll_id).unwrap();
-            assert_eq!(tallies.len(), 3);
-            assert_eq!(tallies[0], 0);
-            assert_eq!(tallies[1], 0);
-            assert_eq!(tallies[2], 0);
+            assert_eq!(contract.is_valid_option(poll_id, 0), Some(true));
+            assert_eq!(contract.is_valid_option(poll_id, 2), Some(true));
+            assert_eq!(contract.is_valid_option(poll_id, 3), Some(false));
+            // There is no abstain sentinel; u32::MAX is just out of range
+            assert_eq!(contract.is_valid_option(poll_id, u32::MAX), Some(false));
+            assert_eq!(contract.is_valid_option(99, 0), None);
+
+            assert_eq!(
+                contract.cast_vote(poll_id, vec![1], [1u8; 32], 3),
+                Err(Error::InvalidVoteOption)
+            );
         }
 
         #[ink::test]
-        fn test_nullifier_tracking() {
+        fn test_nullifier_hidden_in_vote_events() {
             let accounts = ink::env::test::default_accounts();
-            let contract = AnonymousPoll::new(accounts.bob);
+            let mut contract = AnonymousPoll::new(accounts.bob);
 
-            let nullifier = [42u8; 32];
-            let poll_id = 1;
+            let poll_id = contract.create_poll(
+                String::from("Private Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+            assert!(contract.emits_nullifier_in_events(poll_id));
 
-            // Nullifier should not be used initially
-            assert_eq!(contract.is_nullifier_used(poll_id, nullifier), false);
+            let last_vote_cast = || {
+                let events = ink::env::test::recorded_events();
+                let event = events.last().unwrap();
+                <VoteCast as ink::scale::Decode>::decode(&mut &event.data[..]).unwrap()
+            };
+
+            contract.cast_vote(poll_id, vec![1], [1u8; 32], 0).unwrap();
+            assert_eq!(last_vote_cast().nullifier, [1u8; 32]);
+            assert_eq!(contract.set_emit_nullifier_in_events(poll_id, false), Err(Error::PollHasVotes));
+
+            let private_poll = contract.create_poll(
+                String::from("Private Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                0,
+            ).unwrap();
+            contract.set_emit_nullifier_in_events(private_poll, false).unwrap();
+            assert!(!contract.emits_nullifier_in_events(private_poll));
+
+            contract.cast_vote(private_poll, vec![1], [2u8; 32], 1).unwrap();
+            assert_eq!(last_vote_cast().nullifier, [0u8; 32]);
+            // The nullifier is still consumed on-chain
+            assert!(contract.is_nullifier_used(private_poll, [2u8; 32]));
+            assert_eq!(
+                contract.cast_vote(private_poll, vec![1], [2u8; 32], 1),
+                Err(Error::NullifierAlreadyUsed)
+            );
+        }
+
+        #[ink::test]
+        fn test_remaining_vote_capacity() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = create_test_poll(&mut contract);
+            assert_eq!(contract.remaining_vote_capacity(poll_id, 0), Some(u64::MAX));
+
+            contract.vote_tallies.insert((poll_id, 0), &(u64::MAX - 1));
+            assert_eq!(contract.remaining_vote_capacity(poll_id, 0), Some(1));
+
+            contract.cast_vote(poll_id, vec![1], [1u8; 32], 0).unwrap();
+            assert_eq!(contract.remaining_vote_capacity(poll_id, 0), Some(0));
+            assert_eq!(
+                contract.cast_vote(poll_id, vec![1], [2u8; 32], 0),
+                Err(Error::VoteCountOverflow)
+            );
+
+            assert_eq!(contract.remaining_vote_capacity(poll_id, 2), None);
+            assert_eq!(contract.remaining_vote_capacity(99, 0), None);
+        }
+
+        #[ink::test]
+        fn test_new_single_poll() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+
+            let contract = AnonymousPoll::new_single_poll(
+                accounts.bob,
+                String::from("Only Poll"),
+                String::from("One contract per poll"),
+                TEST_ROOT,
+                3,
+                3600,
+            ).unwrap();
+
+            assert_eq!(contract.get_total_polls(), 1);
+            assert_eq!(contract.get_verifier_address(), accounts.bob);
+            let poll = contract.get_polls(vec![1]).unwrap().pop().flatten().unwrap();
+            assert_eq!(poll.title, String::from("Only Poll"));
+            assert_eq!(poll.max_options, 3);
+            assert_eq!(poll.creator, accounts.alice);
+            assert!(poll.is_active);
+
+            assert!(AnonymousPoll::new_single_poll(
+                accounts.bob,
+                String::from("Bad Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                0,
+                0,
+            ).is_err());
+        }
+
+        #[ink::test]
+        fn test_create_poll_rejects_disallowed_default_proof_system() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+            contract.set_allowed_proof_systems(vec![ProofSystem::UltraPlonk]).unwrap();
+
+            let title = String::from("Test Poll");
+            let description = String::from("Test");
+            assert_eq!(
+                contract.validate_poll_config(title.clone(), description.clone(), TEST_ROOT, 2, 0),
+                Err(Error::ProofSystemNotAllowed)
+            );
+            assert_eq!(
+                contract.create_poll(title.clone(), description.clone(), TEST_ROOT, 2, 0),
+                Err(Error::ProofSystemNotAllowed)
+            );
+            assert_eq!(
+                contract.create_poll_with_allowlist(title, description, TEST_ROOT, 2, 0, accounts.charlie),
+                Err(Error::ProofSystemNotAllowed)
+            );
+            assert_eq!(contract.get_total_polls(), 0);
+        }
+
+        #[ink::test]
+        fn test_remaining_vote_capacity_respects_embargo_and_pruning() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = create_test_poll(&mut contract);
+            contract
+                .update_poll_settings(poll_id, PollSettings { embargo_results: true, ..Default::default() })
+                .unwrap();
+            contract.cast_vote(poll_id, vec![1], [1u8; 32], 1).unwrap();
+
+            // Hidden from other callers while the embargo is in force
+            assert_eq!(contract.remaining_vote_capacity(poll_id, 1), Some(u64::MAX - 1));
+            ink::env::test::set_caller(accounts.charlie);
+            assert_eq!(contract.remaining_vote_capacity(poll_id, 1), None);
+
+            ink::env::test::set_caller(accounts.alice);
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1_000);
+            contract.close_poll(poll_id).unwrap();
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(
+                1_000 + RESULT_RETENTION_MS + 1,
+            );
+            contract.prune_poll(poll_id).unwrap();
+
+            // Pruned live rows are read back from the snapshot
+            ink::env::test::set_caller(accounts.charlie);
+            assert_eq!(contract.remaining_vote_capacity(poll_id, 1), Some(u64::MAX - 1));
+            assert_eq!(contract.remaining_vote_capacity(poll_id, 0), Some(u64::MAX));
+        }
+
+        #[ink::test]
+        fn test_recorded_choice_respects_embargo() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = create_test_poll(&mut contract);
+            let settings = PollSettings { embargo_results: true, store_choices: true, ..Default::default() };
+            contract.update_poll_settings(poll_id, settings).unwrap();
+            contract.cast_vote(poll_id, vec![1], [1u8; 32], 1).unwrap();
+
+            assert_eq!(contract.get_recorded_choice(poll_id, [1u8; 32]), Ok(Some(1)));
+            ink::env::test::set_caller(accounts.charlie);
+            assert_eq!(contract.get_recorded_choice(poll_id, [1u8; 32]), Err(Error::ResultsEmbargoed));
+
+            ink::env::test::set_caller(accounts.alice);
+            contract.close_poll(poll_id).unwrap();
+            ink::env::test::set_caller(accounts.charlie);
+            assert_eq!(contract.get_recorded_choice(poll_id, [1u8; 32]), Ok(Some(1)));
+        }
+
+        #[ink::test]
+        fn test_voter_status_hides_choice_under_embargo() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = create_test_poll(&mut contract);
+            let settings = PollSettings { embargo_results: true, store_choices: true, ..Default::default() };
+            contract.update_poll_settings(poll_id, settings).unwrap();
+            contract.cast_vote(poll_id, vec![1], [1u8; 32], 1).unwrap();
+
+            let status = contract.get_voter_status(poll_id, [1u8; 32]).unwrap();
+            assert!(status.has_recorded_choice);
+            assert_eq!(status.recorded_choice, 1);
+
+            ink::env::test::set_caller(accounts.charlie);
+            let status = contract.get_voter_status(poll_id, [1u8; 32]).unwrap();
+            assert!(status.nullifier_used);
+            assert!(!status.has_recorded_choice);
+            assert_eq!(status.recorded_choice, 0);
+        }
+
+        #[ink::test]
+        fn test_grace_period_not_cut_short() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = contract.create_poll(
+                String::from("Graceful Poll"),
+                String::from("Test"),
+                TEST_ROOT,
+                2,
+                10,
+            ).unwrap();
+            contract.add_poll_admin(poll_id, accounts.charlie).unwrap();
+
+            // A poll admin can open a grace period and also settle it
+            ink::env::test::set_caller(accounts.charlie);
+            contract.set_grace_period(poll_id, 5_000).unwrap();
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(12_000);
+            ink::env::test::set_caller(accounts.alice);
+            assert_eq!(contract.finalize_expired(vec![poll_id]), Ok(vec![Err(Error::PollNotEnded)]));
+            contract.cast_vote(poll_id, vec![1], [1u8; 32], 1).unwrap();
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(15_001);
+            assert_eq!(contract.finalize_expired(vec![poll_id]), Ok(vec![Ok(())]));
+
+            ink::env::test::set_caller(accounts.django);
+            assert_eq!(contract.include_late_votes(poll_id), Err(Error::OnlyCreator));
+            ink::env::test::set_caller(accounts.charlie);
+            assert_eq!(contract.include_late_votes(poll_id), Ok(1));
+            assert_eq!(contract.get_all_tallies(poll_id), Ok(vec![0, 1]));
+        }
+
+        #[ink::test]
+        fn test_close_poll_twice_keeps_close_time() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let poll_id = create_test_poll(&mut contract);
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1_000);
+            contract.close_poll(poll_id).unwrap();
+            let events = ink::env::test::recorded_events().len();
+
+            // A second close must not restart the retention clock or re-emit PollClosed
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(5_000);
+            contract.close_poll(poll_id).unwrap();
+            assert_eq!(ink::env::test::recorded_events().len(), events);
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(
+                1_000 + RESULT_RETENTION_MS + 1,
+            );
+            contract.prune_poll(poll_id).unwrap();
+        }
+
+        #[ink::test]
+        fn test_piecemeal_circuit_setters_emit_config_event() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut contract = AnonymousPoll::new(accounts.bob);
+
+            let last_config = || {
+                let events = ink::env::test::recorded_events();
+                let event = events.last().unwrap();
+                <CircuitConfigUpdated as ink::scale::Decode>::decode(&mut &event.data[..])
+                    .unwrap()
+                    .config
+            };
+
+            contract.set_circuit_options(4, true).unwrap();
+            assert_eq!(last_config(), contract.get_circuit_config());
+            assert_eq!(last_config().max_options, 4);
+
+            contract
+                .set_allowed_proof_systems(vec![ProofSystem::UltraHonk, ProofSystem::UltraPlonk])
+                .unwrap();
+            assert_eq!(last_config(), contract.get_circuit_config());
+            assert_eq!(last_config().max_options, 4);
+
+            let events = ink::env::test::recorded_events().len();
+            assert_eq!(contract.set_allowed_proof_systems(vec![]), Err(Error::InvalidPollParameters));
+            let mut config = contract.get_circuit_config();
+            config.allowed_proof_systems = vec![];
+            assert_eq!(contract.set_circuit_config(config), Err(Error::InvalidPollParameters));
+            assert_eq!(ink::env::test::recorded_events().len(), events);
+            assert_eq!(contract.get_allowed_proof_systems().len(), 2);
+        }
+
+        #[ink::test]
+        fn test_nullifier_frontier_stored_per_level() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+            let poll_id = create_test_poll(&mut contract);
+
+            for nullifier in [[1u8; 32], [2u8; 32], [3u8; 32]] {
+                contract.cast_vote(poll_id, vec![1], nullifier, 0).unwrap();
+            }
+
+            // Three leaves: level 0 holds the unpaired third, level 1 the hash of the first two
+            assert_eq!(contract.nullifier_leaf_count.get(poll_id), Some(3));
+            assert_eq!(contract.nullifier_frontier.get((poll_id, 0)), Some([3u8; 32]));
+            assert_eq!(
+                contract.nullifier_frontier.get((poll_id, 1)),
+                Some(contract.hash_pair(&[1u8; 32], &[2u8; 32]))
+            );
+            assert_eq!(contract.nullifier_frontier.get((poll_id, 2)), None);
+        }
+
+        #[ink::test]
+        fn test_proof_format_version() {
+            let accounts = ink::env::test::default_accounts();
+            let mut contract = AnonymousPoll::new(accounts.bob);
+            assert_eq!(contract.proof_format_version(), PROOF_FORMAT_VERSION);
+
+            let poll_id = create_test_poll(&mut contract);
+            let mut compressed = compress_proof(&[1, 2, 3]);
+            compressed[0] = PROOF_FORMAT_VERSION as u8 + 1;
+            assert_eq!(
+                contract.cast_vote_compressed(poll_id, compressed.clone(), [1u8; 32], 0),
+                Err(Error::UnsupportedProofFormat)
+            );
+
+            compressed[0] = PROOF_FORMAT_VERSION as u8;
+            contract.cast_vote_compressed(poll_id, compressed, [1u8; 32], 0).unwrap();
+            assert_eq!(contract.get_all_tallies(poll_id), Ok(vec![1, 0]));
         }
     }
-}
\ No newline at end of file
+}